@@ -0,0 +1,180 @@
+use crate::*;
+
+/// What happens to a gene that has ended up out of its bounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Repair {
+    /// Moves the gene to the nearest bound
+    #[default]
+    Clamp,
+
+    /// Bounces the gene back off the bound it has crossed, as if it was a
+    /// ball bouncing between two walls
+    Reflect,
+
+    /// Lets the gene re-enter from the opposite bound
+    Wrap,
+
+    /// Replaces the gene with a random value within the bounds
+    Resample,
+}
+
+/// Anything that can fix children that don't make sense, applied right
+/// after crossover and mutation.
+pub trait RepairMethod<G = f32>: MaybeSync {
+    fn repair(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>);
+}
+
+/// Lower and upper bound of each gene.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bounds {
+    /// `(min, max)` of each gene; a single range applies to all the genes
+    ranges: Vec<(f32, f32)>,
+
+    repair: Repair,
+}
+
+impl Bounds {
+    pub fn new(ranges: Vec<(f32, f32)>, repair: Repair) -> Self {
+        let bounds = Self { ranges, repair };
+
+        assert!(bounds.is_valid());
+
+        bounds
+    }
+
+    /// Same bounds for all the genes.
+    pub fn uniform(min: f32, max: f32, repair: Repair) -> Self {
+        Self::new(vec![(min, max)], repair)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        !self.ranges.is_empty() && self.ranges.iter().all(|(min, max)| min <= max)
+    }
+
+    pub fn ranges(&self) -> &[(f32, f32)] {
+        &self.ranges
+    }
+
+    pub fn strategy(&self) -> Repair {
+        self.repair
+    }
+
+    /// Returns `(min, max)` of the gene at given position.
+    pub fn range(&self, idx: usize) -> (f32, f32) {
+        if self.ranges.len() == 1 {
+            self.ranges[0]
+        } else {
+            self.ranges[idx]
+        }
+    }
+
+    pub fn contains(&self, chromosome: &Chromosome) -> bool {
+        chromosome.iter().enumerate().all(|(idx, gene)| {
+            let (min, max) = self.range(idx);
+
+            (min..=max).contains(gene)
+        })
+    }
+
+    fn fix(&self, rng: &mut dyn RngCore, gene: f32, min: f32, max: f32) -> f32 {
+        let width = max - min;
+
+        if width == 0.0 {
+            return min;
+        }
+
+        match self.repair {
+            Repair::Clamp => gene.clamp(min, max),
+
+            Repair::Reflect => {
+                let offset = (gene - min).rem_euclid(2.0 * width);
+
+                if offset > width {
+                    max - (offset - width)
+                } else {
+                    min + offset
+                }
+            }
+
+            Repair::Wrap => min + (gene - min).rem_euclid(width),
+
+            Repair::Resample => rng.gen_range(min..=max),
+        }
+    }
+}
+
+impl RepairMethod for Bounds {
+    fn repair(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        assert!(self.ranges.len() == 1 || self.ranges.len() == child.len());
+
+        for (idx, gene) in child.iter_mut().enumerate() {
+            let (min, max) = self.range(idx);
+
+            if !(min..=max).contains(gene) {
+                // (`clamp()` makes sure rounding errors can't push the gene
+                // out of bounds again)
+                *gene = self.fix(rng, *gene, min, max).clamp(min, max);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn repair(repair: Repair) -> Vec<f32> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let bounds = Bounds::new(
+            vec![(0.0, 1.0), (0.0, 1.0), (-1.0, 1.0), (2.0, 2.0)],
+            repair,
+        );
+
+        let mut child: Chromosome = vec![0.5, 1.25, -3.5, 7.0].into_iter().collect();
+        bounds.repair(&mut rng, &mut child);
+
+        assert!(bounds.contains(&child));
+
+        child.into_iter().collect()
+    }
+
+    #[test]
+    fn clamp() {
+        assert_eq!(repair(Repair::Clamp), [0.5, 1.0, -1.0, 2.0]);
+    }
+
+    #[test]
+    fn reflect() {
+        assert_eq!(repair(Repair::Reflect), [0.5, 0.75, 0.5, 2.0]);
+    }
+
+    #[test]
+    fn wrap() {
+        assert_eq!(repair(Repair::Wrap), [0.5, 0.25, 0.5, 2.0]);
+    }
+
+    #[test]
+    fn resample() {
+        let genes = repair(Repair::Resample);
+
+        // Genes within bounds are left alone
+        assert_eq!(genes[0], 0.5);
+        assert_eq!(genes[3], 2.0);
+    }
+
+    #[test]
+    fn uniform() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let bounds = Bounds::uniform(-1.0, 1.0, Repair::Clamp);
+
+        let mut child: Chromosome = vec![-5.0, 0.0, 5.0].into_iter().collect();
+        bounds.repair(&mut rng, &mut child);
+
+        assert_eq!(child.into_iter().collect::<Vec<_>>(), [-1.0, 0.0, 1.0]);
+    }
+}
@@ -1,44 +1,78 @@
+use crate::*;
 use std::iter::FromIterator;
 use std::ops::Index;
 
+/// Sequence of genes - real numbers by default, but bitstrings, integers
+/// and permutations work just as well (see `Gene`).
 #[derive(Clone, Debug, PartialEq)]
-pub struct Chromosome {
-     genes: Vec<f32>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Chromosome<G = f32> {
+     genes: Vec<G>,
 }
 
-impl Chromosome {
+impl<G> Chromosome<G> {
     pub fn len(&self) -> usize {
         self.genes.len()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &f32> {
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    /// Euclidean distance between two chromosomes' genes; used to tell
+    /// apart individuals that belong to different species.
+    pub fn distance(&self, other: &Self) -> f32
+    where
+        G: Gene,
+    {
+        assert_eq!(self.len(), other.len());
+
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| G::distance(a, b))
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &G> {
         self.genes.iter()
     }
 
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut f32> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut G> {
         self.genes.iter_mut()
     }
+
+    pub fn as_slice(&self) -> &[G] {
+        &self.genes
+    }
+
+    /// Genes as a slice, for operators that need to move them around (e.g.
+    /// swap or inversion mutation).
+    pub fn as_mut_slice(&mut self) -> &mut [G] {
+        &mut self.genes
+    }
 }
 
-impl Index<usize> for Chromosome {
-    type Output = f32;
+impl<G> Index<usize> for Chromosome<G> {
+    type Output = G;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.genes[index]
     }
 }
 
-impl FromIterator<f32> for Chromosome {
-    fn from_iter<T: IntoIterator<Item = f32>>(iter: T) -> Self {
+impl<G> FromIterator<G> for Chromosome<G> {
+    fn from_iter<T: IntoIterator<Item = G>>(iter: T) -> Self {
         Self {
             genes: iter.into_iter().collect(),
         }
     }
 }
 
-impl IntoIterator for Chromosome {
-    type Item = f32;
-    type IntoIter = std::vec::IntoIter<f32>;
+impl<G> IntoIterator for Chromosome<G> {
+    type Item = G;
+    type IntoIter = std::vec::IntoIter<G>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.genes.into_iter()
@@ -0,0 +1,20 @@
+use crate::*;
+
+/// User-defined constraint, handled by penalizing the fitness of
+/// individuals that violate it (see `GeneticAlgorithm::with_constraint()`).
+///
+/// Closures taking a chromosome and returning a float are constraints, too.
+pub trait Constraint<G = f32>: MaybeSync {
+    /// How much the chromosome violates the constraint - 0.0 when it's
+    /// satisfied, a positive number (the bigger, the worse) otherwise.
+    fn violation(&self, chromosome: &Chromosome<G>) -> f32;
+}
+
+impl<G, F> Constraint<G> for F
+where
+    F: Fn(&Chromosome<G>) -> f32 + MaybeSync,
+{
+    fn violation(&self, chromosome: &Chromosome<G>) -> f32 {
+        self(chromosome)
+    }
+}
@@ -1,15 +1,19 @@
-mod uniform; 
+mod order;
+mod pmx;
+mod uniform;
 
+pub use self::order::*;
+pub use self::pmx::*;
 pub use self::uniform::*;
 use crate::*;
 
-pub trait CrossoverMethod {
+pub trait CrossoverMethod<G = f32>: MaybeSync {
     fn crossover(
         &self,
         rng: &mut dyn RngCore,
-        parent_a: &Chromosome,
-        parent_b: &Chromosome,
-    ) -> Chromosome;
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G>;
 }
 
 
@@ -0,0 +1,87 @@
+use crate::*;
+
+/// Order crossover (OX) for permutations: the child gets a random segment
+/// of `parent_a` as-is, while the rest of its genes come in the order they
+/// appear in `parent_b` (starting right after the segment), skipping the
+/// ones already taken - so that the child remains a permutation.
+#[derive(Clone, Debug)]
+pub struct OrderCrossover;
+
+impl<G> CrossoverMethod<G> for OrderCrossover
+where
+    G: Gene,
+{
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let len = parent_a.len();
+
+        if len == 0 {
+            return parent_a.clone();
+        }
+
+        let (start, end) = segment(rng, len);
+        let taken = &parent_a.as_slice()[start..=end];
+
+        let mut rest = (0..len)
+            .map(|offset| &parent_b[(end + 1 + offset) % len])
+            .filter(|gene| !taken.contains(gene));
+
+        let mut genes: Vec<Option<G>> = vec![None; len];
+
+        for idx in start..=end {
+            genes[idx] = Some(parent_a[idx].clone());
+        }
+
+        for offset in 0..len - taken.len() {
+            genes[(end + 1 + offset) % len] = rest.next().cloned();
+        }
+
+        genes
+            .into_iter()
+            .map(|gene| gene.expect("parents are not permutations of each other"))
+            .collect()
+    }
+}
+
+/// Picks a random, non-empty segment `start..=end` of a chromosome.
+pub(crate) fn segment(rng: &mut dyn RngCore, len: usize) -> (usize, usize) {
+    let a = rng.gen_range(0..len);
+    let b = rng.gen_range(0..len);
+
+    (a.min(b), a.max(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome<usize> = (0..10).collect();
+        let parent_b: Chromosome<usize> = (0..10).rev().collect();
+
+        for _ in 0..100 {
+            let child = OrderCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+            let mut genes = child.as_slice().to_vec();
+            genes.sort();
+
+            assert_eq!(genes, parent_a.as_slice());
+        }
+
+        // Segment `4..=5` comes from `parent_a`, the rest gets filled
+        // starting from position 6 with what's left of `parent_b`
+        let child = OrderCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        assert_eq!(child.as_slice(), [9, 8, 7, 6, 4, 5, 3, 2, 1, 0]);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::*;
+
+/// Partially mapped crossover (PMX) for permutations: the child gets a
+/// random segment of `parent_a` as-is and the rest of its genes from
+/// `parent_b` on the same positions - genes that would then appear twice
+/// are replaced by following the mapping the segment establishes between
+/// both parents.
+#[derive(Clone, Debug)]
+pub struct PmxCrossover;
+
+impl<G> CrossoverMethod<G> for PmxCrossover
+where
+    G: Gene,
+{
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let len = parent_a.len();
+
+        if len == 0 {
+            return parent_a.clone();
+        }
+
+        let (start, end) = segment(rng, len);
+        let taken = &parent_a.as_slice()[start..=end];
+
+        (0..len)
+            .map(|idx| {
+                if (start..=end).contains(&idx) {
+                    return parent_a[idx].clone();
+                }
+
+                let mut gene = &parent_b[idx];
+
+                // (since both parents are permutations, this terminates
+                // after at most `taken.len()` hops)
+                for _ in 0..=taken.len() {
+                    match taken.iter().position(|taken| taken == gene) {
+                        Some(pos) => gene = &parent_b[start + pos],
+                        None => return gene.clone(),
+                    }
+                }
+
+                panic!("parents are not permutations of each other")
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome<usize> = vec![8, 4, 7, 3, 6, 2, 5, 1, 9, 0].into_iter().collect();
+        let parent_b: Chromosome<usize> = (0..10).collect();
+
+        for _ in 0..100 {
+            let child = PmxCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+            let mut genes = child.as_slice().to_vec();
+            genes.sort();
+
+            assert_eq!(genes, parent_b.as_slice());
+        }
+
+        // Segment `3..=5` (that is: `3, 6, 2`) comes from `parent_a`, which
+        // maps 2 to 5 and 6 to 4 for the genes coming from `parent_b`
+        let child = PmxCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        assert_eq!(child.as_slice(), [0, 1, 5, 3, 6, 2, 4, 7, 8, 9]);
+    }
+}
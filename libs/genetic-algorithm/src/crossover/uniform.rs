@@ -3,19 +3,22 @@ use crate::*;
 #[derive(Clone, Debug)]
 pub struct UniformCrossover;
 
-impl CrossoverMethod for UniformCrossover {
+impl<G> CrossoverMethod<G> for UniformCrossover
+where
+    G: Gene,
+{
     fn crossover(
         &self,
         rng: &mut dyn RngCore,
-        parent_a: &Chromosome,
-        parent_b: &Chromosome,
-    ) -> Chromosome {
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>,
+    ) -> Chromosome<G> {
         assert_eq!(parent_a.len(), parent_b.len());
 
         parent_a
             .iter()
             .zip(parent_b.iter())
-            .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b })
+            .map(|(a, b)| if rng.gen_bool(0.5) { a.clone() } else { b.clone() })
             .collect()
     }
 }
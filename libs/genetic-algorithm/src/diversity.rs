@@ -0,0 +1,252 @@
+use crate::*;
+
+/// Number of bins `Diversity::new()` sorts genes into when measuring their
+/// entropy
+pub const ENTROPY_BINS: usize = 10;
+
+/// Summary of how diverse a population is - all the measures drop to zero
+/// once it has converged to a single chromosome.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diversity {
+    pairwise_distance: f32,
+    gene_variance: f32,
+    gene_entropy: f32,
+    centroid_distance: f32,
+}
+
+impl Diversity {
+    pub fn new<I>(population: &[I]) -> Self
+    where
+        I: Individual,
+    {
+        assert!(!population.is_empty());
+
+        let variance = gene_variance(population);
+
+        Self {
+            pairwise_distance: pairwise_distance(population),
+            gene_variance: variance.iter().sum::<f32>() / variance.len().max(1) as f32,
+            gene_entropy: gene_entropy(population, ENTROPY_BINS),
+            centroid_distance: centroid_distance(population),
+        }
+    }
+
+    /// See `pairwise_distance()`
+    pub fn pairwise_distance(&self) -> f32 {
+        self.pairwise_distance
+    }
+
+    /// Average of `gene_variance()` over all the genes
+    pub fn gene_variance(&self) -> f32 {
+        self.gene_variance
+    }
+
+    /// See `gene_entropy()`
+    pub fn gene_entropy(&self) -> f32 {
+        self.gene_entropy
+    }
+
+    /// See `centroid_distance()`
+    pub fn centroid_distance(&self) -> f32 {
+        self.centroid_distance
+    }
+}
+
+/// Average (Euclidean) distance between two chromosomes of given
+/// population.
+///
+/// Takes `O(n^2)` comparisons, so it's the most accurate measure, but also
+/// the slowest one.
+pub fn pairwise_distance<I, G>(population: &[I]) -> f32
+where
+    I: Individual<G>,
+    G: Gene,
+{
+    if population.len() < 2 {
+        return 0.0;
+    }
+
+    let mut sum = 0.0;
+
+    for (idx, a) in population.iter().enumerate() {
+        for b in &population[idx + 1..] {
+            sum += a.chromosome().distance(b.chromosome());
+        }
+    }
+
+    let pairs = population.len() * (population.len() - 1) / 2;
+
+    sum / pairs as f32
+}
+
+/// Variance of each gene across given population.
+pub fn gene_variance<I>(population: &[I]) -> Vec<f32>
+where
+    I: Individual,
+{
+    let centroid = centroid(population);
+    let mut variance = vec![0.0; centroid.len()];
+
+    for individual in population {
+        for ((variance, gene), mean) in variance
+            .iter_mut()
+            .zip(individual.chromosome().iter())
+            .zip(&centroid)
+        {
+            *variance += (gene - mean).powi(2);
+        }
+    }
+
+    for variance in &mut variance {
+        *variance /= population.len() as f32;
+    }
+
+    variance
+}
+
+/// Shannon entropy of genes, averaged over all the genes.
+///
+/// Each gene's values get sorted into `bins` equally wide bins spanning
+/// from the smallest to the largest value found in the population; entropy
+/// is then normalized, so that it's 0.0 when everybody has the same gene
+/// and 1.0 when the values are spread evenly across all the bins.
+pub fn gene_entropy<I>(population: &[I], bins: usize) -> f32
+where
+    I: Individual,
+{
+    assert!(bins >= 2);
+
+    let Some(first) = population.first() else {
+        return 0.0;
+    };
+
+    let genes = first.chromosome().len();
+
+    if genes == 0 {
+        return 0.0;
+    }
+
+    let mut counts = vec![0; bins];
+    let mut sum = 0.0;
+
+    for gene in 0..genes {
+        let values = || {
+            population
+                .iter()
+                .map(|individual| individual.chromosome()[gene])
+        };
+        let min = values().fold(f32::INFINITY, f32::min);
+        let max = values().fold(f32::NEG_INFINITY, f32::max);
+
+        if max <= min {
+            continue;
+        }
+
+        counts.fill(0);
+
+        for value in values() {
+            let bin = ((value - min) / (max - min) * bins as f32) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+
+        sum -= counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f32 / population.len() as f32;
+                p * p.ln()
+            })
+            .sum::<f32>();
+    }
+
+    sum / (genes as f32 * (bins as f32).ln())
+}
+
+/// Average (Euclidean) distance between chromosomes of given population and
+/// their centroid - a cheaper, `O(n)` alternative to `pairwise_distance()`.
+pub fn centroid_distance<I>(population: &[I]) -> f32
+where
+    I: Individual,
+{
+    if population.is_empty() {
+        return 0.0;
+    }
+
+    let centroid: Chromosome = centroid(population).into_iter().collect();
+
+    population
+        .iter()
+        .map(|individual| individual.chromosome().distance(&centroid))
+        .sum::<f32>()
+        / population.len() as f32
+}
+
+/// Average of all the chromosomes, gene by gene.
+fn centroid<I>(population: &[I]) -> Vec<f32>
+where
+    I: Individual,
+{
+    let Some(first) = population.first() else {
+        return Vec::new();
+    };
+
+    let mut centroid = vec![0.0; first.chromosome().len()];
+
+    for individual in population {
+        for (mean, gene) in centroid.iter_mut().zip(individual.chromosome().iter()) {
+            *mean += gene;
+        }
+    }
+
+    for mean in &mut centroid {
+        *mean /= population.len() as f32;
+    }
+
+    centroid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn population() -> Vec<TestIndividual> {
+        [[0.0, 1.0], [2.0, 1.0], [0.0, 1.0], [2.0, 1.0]]
+            .into_iter()
+            .map(|genes| TestIndividual::create(genes.into_iter().collect()))
+            .collect()
+    }
+
+    #[test]
+    fn measures() {
+        let population = population();
+
+        // Distances between the pairs: 2, 0, 2, 2, 0, 2
+        approx::assert_relative_eq!(pairwise_distance(&population), 8.0 / 6.0);
+        assert_eq!(gene_variance(&population), [1.0, 0.0]);
+
+        // First gene is split evenly between the two bins, while the other
+        // one is always the same
+        assert_eq!(gene_entropy(&population, 2), 0.5);
+
+        // Centroid is [1.0, 1.0]
+        assert_eq!(centroid_distance(&population), 1.0);
+
+        let diversity = Diversity::new(&population);
+
+        approx::assert_relative_eq!(diversity.pairwise_distance(), 8.0 / 6.0);
+        assert_eq!(diversity.gene_variance(), 0.5);
+        assert_eq!(diversity.centroid_distance(), 1.0);
+    }
+
+    #[test]
+    fn converged() {
+        let population: Vec<_> = (0..5)
+            .map(|_| TestIndividual::create([1.0, 2.0, 3.0].into_iter().collect()))
+            .collect();
+
+        let diversity = Diversity::new(&population);
+
+        assert_eq!(diversity, Diversity::default());
+    }
+}
@@ -0,0 +1,70 @@
+use crate::*;
+
+/// Anything a chromosome can be made of.
+///
+/// Besides the usual real numbers, that's booleans (for bitstrings),
+/// integers (for bounded integer problems - see `ResetMutation`) and
+/// `usize` (for permutations - see `OrderCrossover` and `PmxCrossover`).
+pub trait Gene: Clone + PartialEq + MaybeSync {
+    /// Squared distance between two genes; `Chromosome::distance()` is the
+    /// square root of these summed up.
+    fn distance(a: &Self, b: &Self) -> f32;
+}
+
+impl Gene for f32 {
+    fn distance(a: &Self, b: &Self) -> f32 {
+        (a - b) * (a - b)
+    }
+}
+
+impl Gene for f64 {
+    fn distance(a: &Self, b: &Self) -> f32 {
+        ((a - b) * (a - b)) as f32
+    }
+}
+
+/// Bits are either the same or not, which makes the distance between two
+/// bitstrings the square root of their Hamming distance.
+impl Gene for bool {
+    fn distance(a: &Self, b: &Self) -> f32 {
+        if a == b {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+macro_rules! integer_genes {
+    ($($ty:ty),*) => {
+        $(
+            impl Gene for $ty {
+                fn distance(a: &Self, b: &Self) -> f32 {
+                    let diff = *a as f64 - *b as f64;
+
+                    (diff * diff) as f32
+                }
+            }
+        )*
+    };
+}
+
+integer_genes!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        let a: Chromosome<bool> = vec![true, false, true, true].into_iter().collect();
+        let b: Chromosome<bool> = vec![false, false, true, false].into_iter().collect();
+
+        approx::assert_relative_eq!(a.distance(&b), 2.0f32.sqrt());
+
+        let a: Chromosome<u8> = vec![0, 10].into_iter().collect();
+        let b: Chromosome<u8> = vec![3, 6].into_iter().collect();
+
+        approx::assert_relative_eq!(a.distance(&b), 5.0);
+    }
+}
@@ -1,8 +1,8 @@
 use crate::*;
 
-pub trait Individual {
-    fn create(chromosome: Chromosome) -> Self;
-    fn chromosome(&self) -> &Chromosome;
+pub trait Individual<G = f32>: MaybeSync {
+    fn create(chromosome: Chromosome<G>) -> Self;
+    fn chromosome(&self) -> &Chromosome<G>;
     fn fitness(&self) -> f32;
 }
 
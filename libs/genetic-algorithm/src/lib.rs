@@ -1,59 +1,423 @@
+mod bounds;
 mod chromosome;
+mod constraint;
 mod crossover;
+mod diversity;
+mod gene;
 mod individual;
 mod mutation;
+mod novelty;
+mod observer;
+mod optimizer;
+mod parallel;
+mod restart;
+mod scaling;
 mod selection;
+mod speciation;
+mod statistics;
+mod steady_state;
+mod termination;
 
+pub use self::bounds::*;
 pub use self::chromosome::*;
+pub use self::constraint::*;
 pub use self::crossover::*;
+pub use self::diversity::*;
+pub use self::gene::*;
 pub use self::individual::*;
 pub use self::mutation::*;
+pub use self::novelty::*;
+pub use self::observer::*;
+pub use self::optimizer::*;
+pub use self::parallel::*;
+pub use self::restart::*;
+pub use self::scaling::*;
 pub use self::selection::*;
+pub use self::speciation::*;
+pub use self::statistics::*;
+pub use self::steady_state::*;
+pub use self::termination::*;
 use rand::seq::SliceRandom;
+#[cfg(feature = "parallel")]
+use rand::SeedableRng;
 use rand::{Rng, RngCore};
+#[cfg(feature = "parallel")]
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::sync::Mutex;
 
-pub struct GeneticAlgorithm<S> {
+/// Genetic algorithm evolving chromosomes made of `G`s - real numbers by
+/// default, but crossover and mutation methods can be picked for other kinds
+/// of genes as well (e.g. `OrderCrossover` and `SwapMutation` for
+/// permutations).
+pub struct GeneticAlgorithm<S, G = f32> {
     selection_method: S,
-    crossover_method: Box<dyn CrossoverMethod>,
-    mutation_method: Box<dyn MutationMethod>,
+    crossover_method: Box<dyn CrossoverMethod<G>>,
+    mutation_method: Box<dyn MutationMethod<G>>,
+    repair_method: Option<Box<dyn RepairMethod<G>>>,
+    constraints: Vec<(Box<dyn Constraint<G>>, f32)>,
+    scaling: Option<Scaling>,
+    speciation: Option<Speciation>,
+    observers: Vec<Box<dyn Observer<G>>>,
+    generator: Option<Box<dyn Generator<G>>>,
+
+    /// Fraction of each new generation that's replaced with immigrants
+    immigrants: f32,
+
+    restart: Option<Restart>,
+
+    /// (behind a mutex, since it's updated by `evolve()`, which only
+    /// borrows the algorithm)
+    plateau: Mutex<Plateau>,
+
+    /// Number of generations evolved so far, which is what the scaling's
+    /// temperature schedule goes by
+    generation: usize,
 }
 
-impl<S> GeneticAlgorithm<S>
+impl<S, G> GeneticAlgorithm<S, G>
 where
     S: SelectionMethod,
+    G: Gene,
 {
     pub fn new(
         selection_method: S,
-        crossover_method: impl CrossoverMethod + 'static,
-        mutation_method: impl MutationMethod + 'static,
+        crossover_method: impl CrossoverMethod<G> + 'static,
+        mutation_method: impl MutationMethod<G> + 'static,
     ) -> Self {
         Self {
             selection_method,
             crossover_method: Box::new(crossover_method),
             mutation_method: Box::new(mutation_method),
+            repair_method: None,
+            constraints: Vec::new(),
+            scaling: None,
+            speciation: None,
+            observers: Vec::new(),
+            generator: None,
+            immigrants: 0.0,
+            restart: None,
+            plateau: Default::default(),
+            generation: 0,
         }
     }
 
-    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I>
+    /// Repairs each child right after crossover and mutation - e.g. with
+    /// `Bounds`, to keep genes within their allowed ranges.
+    pub fn with_repair(mut self, repair_method: impl RepairMethod<G> + 'static) -> Self {
+        self.repair_method = Some(Box::new(repair_method));
+        self
+    }
+
+    /// Penalizes individuals violating given constraint: during selection,
+    /// their fitness gets decreased by `penalty` times the violation (but
+    /// never below zero, so that roulette keeps working).
+    ///
+    /// Statistics still report the raw, unpenalized fitness.
+    pub fn with_constraint(
+        mut self,
+        constraint: impl Constraint<G> + 'static,
+        penalty: f32,
+    ) -> Self {
+        assert!(penalty >= 0.0);
+
+        self.constraints.push((Box::new(constraint), penalty));
+        self
+    }
+
+    /// Transforms fitness before selection (after the constraints' penalties
+    /// are applied).
+    pub fn with_scaling(mut self, scaling: Scaling) -> Self {
+        assert!(scaling.is_valid());
+
+        self.scaling = Some(scaling);
+        self
+    }
+
+    /// Makes the algorithm pick up from given generation, e.g. after it's
+    /// been restored from a save.
+    ///
+    /// Generation is advanced by `Optimizer::evolve()`, but not by
+    /// `evolve()` - callers using the latter together with Boltzmann
+    /// scaling need to keep track of generations on their own.
+    pub fn with_generation(mut self, generation: usize) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Adds an observer that gets notified about everything that happens
+    /// during evolution; pass an `Arc` to keep access to it.
+    pub fn with_observer(mut self, observer: impl Observer<G> + 'static) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    /// Divides population into species before breeding; parents are then
+    /// chosen only within their own species, and each species gets a
+    /// number of children proportional to its shared fitness.
+    pub fn with_speciation(mut self, speciation: Speciation) -> Self {
+        self.speciation = Some(speciation);
+        self
+    }
+
+    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
     where
-        I: Individual,
+        I: Individual<G>,
     {
         assert!(!population.is_empty());
 
-        let new_population = (0..population.len())
-            .map(|_| {
-                let parent_a = self.selection_method.select(rng, population).chromosome();
-                let parent_b = self.selection_method.select(rng, population).chromosome();
+        assert!(
+            self.generator.is_some() || (self.immigrants == 0.0 && self.restart.is_none()),
+            "immigrants and restarts require a generator"
+        );
+
+        for observer in &self.observers {
+            observer.generation_started(self.generation, population.len());
+        }
+
+        let (mut new_population, species) = if self.adjusts_fitness() {
+            self.breed_generation(rng, &self.adjust(population))
+        } else {
+            self.breed_generation(rng, population)
+        };
+
+        if self.immigrants > 0.0 {
+            self.immigrate(rng, &mut new_population);
+        }
 
-                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+        let stats = Statistics::new(population, species);
 
-                self.mutation_method.mutate(rng, &mut child);
+        self.restart(rng, population, &mut new_population, &stats);
 
-                I::create(child)
+        for observer in &self.observers {
+            observer.generation_finished(self.generation, &stats);
+        }
+
+        (new_population, stats)
+    }
+
+    /// Breeds the whole next generation; returns it together with the
+    /// number of species it's been bred from.
+    fn breed_generation<P, I>(&self, rng: &mut dyn RngCore, parents: &[P]) -> (Vec<I>, usize)
+    where
+        P: Individual<G>,
+        I: Individual<G>,
+    {
+        let Some(speciation) = &self.speciation else {
+            let origins: Vec<_> = (0..parents.len()).collect();
+            let new_population = self.breed_many(rng, parents, &origins, 0, parents.len());
+
+            return (new_population, 1);
+        };
+
+        let species = speciation.speciate(parents);
+        let allotment = speciation.allot(parents, &species, parents.len());
+        let mut first = 0;
+
+        let new_population = species
+            .iter()
+            .zip(allotment)
+            .flat_map(|(species, children)| {
+                let brood = self.breed_many(
+                    rng,
+                    &species.shared(parents),
+                    species.members(),
+                    first,
+                    children,
+                );
+
+                first += children;
+                brood
             })
             .collect();
 
-        new_population
+        (new_population, species.len())
+    }
+
+    fn adjusts_fitness(&self) -> bool {
+        !self.constraints.is_empty() || self.scaling.is_some()
+    }
+
+    /// Returns population with each individual's fitness decreased by the
+    /// penalties for the constraints it violates, and then scaled.
+    fn adjust<'a, I>(&self, population: &'a [I]) -> Vec<Shared<'a, I>>
+    where
+        I: Individual<G>,
+    {
+        let mut fitness: Vec<f32> = population
+            .iter()
+            .map(|individual| {
+                let penalty: f32 = self
+                    .constraints
+                    .iter()
+                    .map(|(constraint, penalty)| {
+                        penalty * constraint.violation(individual.chromosome()).max(0.0)
+                    })
+                    .sum();
+
+                individual.fitness() - penalty
+            })
+            .collect();
+
+        match &self.scaling {
+            Some(scaling) => scaling.apply(&mut fitness, self.generation),
+
+            None => {
+                for fitness in &mut fitness {
+                    *fitness = fitness.max(0.0);
+                }
+            }
+        }
+
+        population
+            .iter()
+            .zip(fitness)
+            .map(|(individual, fitness)| Shared::new(individual, fitness))
+            .collect()
+    }
+
+    /// Breeds `count` children out of `parents`; `origins` are parents'
+    /// indices in the whole population and `first` is the index the first
+    /// child is going to have in the new one (both are for observers).
+    #[cfg(not(feature = "parallel"))]
+    fn breed_many<P, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        parents: &[P],
+        origins: &[usize],
+        first: usize,
+        count: usize,
+    ) -> Vec<I>
+    where
+        P: Individual<G>,
+        I: Individual<G>,
+    {
+        (0..count)
+            .map(|idx| self.breed(rng, parents, origins, first + idx))
+            .collect()
+    }
+
+    /// Breeds children on all available threads.
+    ///
+    /// Each child gets its own random number generator - seeded once from
+    /// `rng` and then split into streams by the child's index - so that the
+    /// outcome doesn't depend on how many threads there are or in which
+    /// order they happen to run.
+    #[cfg(feature = "parallel")]
+    fn breed_many<P, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        parents: &[P],
+        origins: &[usize],
+        first: usize,
+        count: usize,
+    ) -> Vec<I>
+    where
+        P: Individual<G>,
+        I: Individual<G>,
+    {
+        let seed = rng.gen();
+
+        (0..count)
+            .into_par_iter()
+            .map(|idx| {
+                let mut rng = ChaCha8Rng::from_seed(seed);
+                rng.set_stream(idx as u64);
+
+                self.breed(&mut rng, parents, origins, first + idx)
+            })
+            .collect()
+    }
+
+    fn breed<P, I>(&self, rng: &mut dyn RngCore, parents: &[P], origins: &[usize], idx: usize) -> I
+    where
+        P: Individual<G>,
+        I: Individual<G>,
+    {
+        let (parent_a, origin_a) = self.select(rng, parents, origins);
+        let (parent_b, origin_b) = self.select(rng, parents, origins);
+
+        self.offspring(rng, (parent_a, origin_a), (parent_b, origin_b), idx)
+    }
+
+    /// Crosses given parents over, then mutates and repairs their child;
+    /// parents come together with their indices in the whole population
+    /// (for observers).
+    fn offspring<P, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        (parent_a, origin_a): (&P, usize),
+        (parent_b, origin_b): (&P, usize),
+        idx: usize,
+    ) -> I
+    where
+        P: Individual<G>,
+        I: Individual<G>,
+    {
+        let mut child =
+            self.crossover_method
+                .crossover(rng, parent_a.chromosome(), parent_b.chromosome());
+
+        if self.observers.is_empty() {
+            self.mutation_method.mutate(rng, &mut child);
+        } else {
+            for observer in &self.observers {
+                observer.crossed_over(origin_a, origin_b, &child);
+            }
+
+            let before = child.clone();
+
+            self.mutation_method.mutate(rng, &mut child);
+
+            for observer in &self.observers {
+                observer.mutated(&before, &child);
+            }
+        }
+
+        if let Some(repair_method) = &self.repair_method {
+            repair_method.repair(rng, &mut child);
+        }
+
+        for observer in &self.observers {
+            observer.created(idx, [origin_a, origin_b], &child);
+        }
+
+        I::create(child)
+    }
+
+    /// Selects a parent; returns it together with its index in the whole
+    /// population (which is only looked up when somebody's observing).
+    fn select<'a, P>(
+        &self,
+        rng: &mut dyn RngCore,
+        parents: &'a [P],
+        origins: &[usize],
+    ) -> (&'a P, usize)
+    where
+        P: Individual<G>,
+    {
+        let parent = self.selection_method.select(rng, parents);
+
+        if self.observers.is_empty() {
+            return (parent, 0);
+        }
+
+        let origin = parents
+            .iter()
+            .position(|candidate| std::ptr::eq(candidate, parent))
+            .map(|idx| origins[idx])
+            .expect("selection method returned somebody from outside the population");
+
+        for observer in &self.observers {
+            observer.selected(origin, parent.fitness());
+        }
+
+        (parent, origin)
     }
 }
 
@@ -86,9 +450,12 @@ mod tests {
         ];
 
         for _ in 0..10 {
-            population = ga.evolve(&mut rng, &population);
+            (population, _) = ga.evolve(&mut rng, &population);
         }
 
+        // Parallel breeding gives each child its own rng, so it draws
+        // different numbers than the sequential one
+        #[cfg(not(feature = "parallel"))]
         let expected_population = vec![
             individual(&[0.44769490, 2.0648358, 4.3058133]),
             individual(&[1.21268670, 1.5538777, 2.8869110]),
@@ -96,6 +463,269 @@ mod tests {
             individual(&[0.95909685, 2.4618788, 4.0247330]),
         ];
 
+        #[cfg(feature = "parallel")]
+        let expected_population = vec![
+            individual(&[1.4335790, 1.5019912, 2.3633142]),
+            individual(&[1.5588951, 1.6183305, 2.7614322]),
+            individual(&[1.5264505, 0.5037043, 2.9541767]),
+            individual(&[1.4622455, 1.2100760, 3.2836936]),
+        ];
+
         assert_eq!(population, expected_population);
     }
+
+    #[test]
+    fn speciation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        )
+        .with_speciation(Speciation::new(2.0));
+
+        let population = vec![
+            individual(&[0.0, 1.0, 0.0]),
+            individual(&[1.0, 1.0, 1.0]),
+            individual(&[1.0, 2.0, 1.0]),
+            individual(&[8.0, 0.0, -6.0]),
+            individual(&[8.0, 1.0, -6.0]),
+            individual(&[9.0, 0.0, -6.0]),
+        ];
+
+        let (population, stats) = ga.evolve(&mut rng, &population);
+
+        assert_eq!(population.len(), 6);
+        assert_eq!(stats.species(), 2);
+
+        // Fitness-wise both species are equally good on average, so both
+        // should have kept half of the population - and since parents only
+        // mate within their own species, no child can be a cross-species
+        // hybrid
+        let (near, far): (Vec<_>, Vec<_>) = population
+            .iter()
+            .partition(|individual| individual.chromosome()[0] < 4.0);
+
+        assert_eq!(near.len(), 3);
+        assert_eq!(far.len(), 3);
+    }
+
+    #[test]
+    fn bounds() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let bounds = Bounds::uniform(0.0, 2.0, Repair::Reflect);
+
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        )
+        .with_repair(bounds.clone());
+
+        let mut population: Vec<_> = (0..16).map(|_| individual(&[1.0, 1.0, 1.0])).collect();
+
+        for _ in 0..20 {
+            (population, _) = ga.evolve(&mut rng, &population);
+        }
+
+        // Fitness pushes genes up, but they can't go past the bound
+        assert!(population
+            .iter()
+            .all(|individual| bounds.contains(individual.chromosome())));
+
+        assert!(population
+            .iter()
+            .any(|individual| individual.chromosome().iter().any(|&gene| gene > 1.8)));
+    }
+
+    #[test]
+    fn constraints() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        // Fitness is the sum of genes, but their sum can't really exceed 6
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        )
+        .with_constraint(
+            |chromosome: &Chromosome| chromosome.iter().sum::<f32>() - 6.0,
+            10.0,
+        );
+
+        let mut population: Vec<_> = (0..32)
+            .map(|n| individual(&[n as f32 / 16.0, 1.0, 2.0]))
+            .collect();
+
+        for _ in 0..30 {
+            (population, _) = ga.evolve(&mut rng, &population);
+        }
+
+        let (_, stats) = ga.evolve(&mut rng, &population);
+
+        assert!(stats.avg_fitness() > 5.0);
+        assert!(stats.avg_fitness() < 6.5);
+    }
+
+    #[test]
+    fn scaling() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        )
+        .with_scaling(Scaling::Rank)
+        .with_generation(5);
+
+        // Roulette can't deal with negative fitness on its own, but ranks
+        // are always positive
+        let population = vec![
+            individual(&[-3.0, -1.0]),
+            individual(&[-2.0, -2.0]),
+            individual(&[-1.0, -1.0]),
+        ];
+
+        let (population, stats) = Optimizer::evolve(&mut ga, &mut rng, &population);
+
+        assert_eq!(population.len(), 3);
+        approx::assert_relative_eq!(stats.min_fitness(), -4.0);
+        assert_eq!(ga.generation(), 6);
+    }
+
+    /// Bitstring scored by the number of ones it contains
+    #[derive(Clone, Debug)]
+    struct OneMax(Chromosome<bool>);
+
+    impl Individual<bool> for OneMax {
+        fn create(chromosome: Chromosome<bool>) -> Self {
+            Self(chromosome)
+        }
+
+        fn chromosome(&self) -> &Chromosome<bool> {
+            &self.0
+        }
+
+        fn fitness(&self) -> f32 {
+            self.0.iter().filter(|&&bit| bit).count() as f32
+        }
+    }
+
+    #[test]
+    fn bitstrings() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            BitFlipMutation::new(0.01),
+        );
+
+        let mut population: Vec<_> = (0..32)
+            .map(|_| OneMax((0..32).map(|_| rng.gen_bool(0.5)).collect()))
+            .collect();
+
+        let (_, before) = ga.evolve(&mut rng, &population);
+
+        for _ in 0..50 {
+            (population, _) = ga.evolve(&mut rng, &population);
+        }
+
+        let (_, after) = ga.evolve(&mut rng, &population);
+
+        assert!(after.avg_fitness() > before.avg_fitness() + 4.0);
+    }
+
+    /// Permutation scored by how many of its neighbours are in order -
+    /// exponentially, so that roulette has a clear favourite
+    #[derive(Clone, Debug)]
+    struct Sorted(Chromosome<usize>);
+
+    impl Individual<usize> for Sorted {
+        fn create(chromosome: Chromosome<usize>) -> Self {
+            Self(chromosome)
+        }
+
+        fn chromosome(&self) -> &Chromosome<usize> {
+            &self.0
+        }
+
+        fn fitness(&self) -> f32 {
+            let genes = self.0.as_slice();
+
+            2.0f32.powi(genes.windows(2).filter(|pair| pair[0] < pair[1]).count() as i32)
+        }
+    }
+
+    #[test]
+    fn permutations() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            OrderCrossover,
+            InversionMutation::new(0.2),
+        );
+
+        let mut population: Vec<_> = (0..32)
+            .map(|_| {
+                let mut genes: Vec<usize> = (0..10).collect();
+                genes.shuffle(&mut rng);
+
+                Sorted(genes.into_iter().collect())
+            })
+            .collect();
+
+        let (_, before) = ga.evolve(&mut rng, &population);
+
+        for _ in 0..50 {
+            (population, _) = ga.evolve(&mut rng, &population);
+        }
+
+        let (_, after) = ga.evolve(&mut rng, &population);
+
+        assert!(after.avg_fitness() > before.avg_fitness());
+
+        // Children are still permutations
+        for individual in &population {
+            let mut genes = individual.chromosome().as_slice().to_vec();
+            genes.sort();
+
+            assert_eq!(genes, (0..10).collect::<Vec<_>>());
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_does_not_depend_on_threads() {
+        let evolve = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+
+            pool.install(|| {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+                let ga = GeneticAlgorithm::new(
+                    RouletteWheelSelection,
+                    UniformCrossover,
+                    GaussianMutation::new(0.5, 0.5),
+                );
+
+                let mut population: Vec<_> =
+                    (0..64).map(|n| individual(&[n as f32, 1.0, 2.0])).collect();
+
+                for _ in 0..10 {
+                    (population, _) = ga.evolve(&mut rng, &population);
+                }
+
+                population
+            })
+        };
+
+        assert_eq!(evolve(1), evolve(4));
+    }
 }
@@ -1,8 +1,16 @@
+mod bit_flip;
 mod gaussian;
+mod inversion;
+mod reset;
+mod swap;
 
+pub use self::bit_flip::*;
 pub use self::gaussian::*;
+pub use self::inversion::*;
+pub use self::reset::*;
+pub use self::swap::*;
 use crate::*;
 
-pub trait MutationMethod {
-    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome);
+pub trait MutationMethod<G = f32>: MaybeSync {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>);
 }
@@ -0,0 +1,51 @@
+use crate::*;
+
+/// Mutation for bitstrings: flips each bit with given probability.
+#[derive(Clone, Debug)]
+pub struct BitFlipMutation {
+    /// Probability of flipping a bit:
+    /// - 0.0 = no bits will be touched
+    /// - 1.0 = all bits will be flipped
+    chance: f32,
+}
+
+impl BitFlipMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+
+        Self { chance }
+    }
+}
+
+impl MutationMethod<bool> for BitFlipMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<bool>) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as f64) {
+                *gene = !*gene;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn flipped(chance: f32) -> usize {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<bool> = (0..100).map(|_| false).collect();
+
+        BitFlipMutation::new(chance).mutate(&mut rng, &mut child);
+
+        child.iter().filter(|&&gene| gene).count()
+    }
+
+    #[test]
+    fn test() {
+        assert_eq!(flipped(0.0), 0);
+        assert_eq!(flipped(0.25), 25);
+        assert_eq!(flipped(1.0), 100);
+    }
+}
@@ -15,7 +15,7 @@ pub struct GaussianMutation {
 
 impl GaussianMutation {
     pub fn new(chance: f32, coeff: f32) -> Self {
-        assert!(chance >= 0.0 && chance <= 1.0);
+        assert!((0.0..=1.0).contains(&chance));
 
         Self { chance, coeff }
     }
@@ -33,6 +33,18 @@ impl MutationMethod for GaussianMutation {
     }
 }
 
+impl MutationMethod<f64> for GaussianMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<f64>) {
+        for gene in child.iter_mut() {
+            let sign = if rng.gen_bool(0.5) { -1.0 } else { 1.0 };
+
+            if rng.gen_bool(self.chance as f64) {
+                *gene += sign * self.coeff as f64 * rng.gen::<f64>();
+            }
+        }
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
@@ -0,0 +1,63 @@
+use crate::*;
+
+/// Mutation for permutations: reverses a random segment of the chromosome,
+/// which (for e.g. routes) keeps most of the neighbouring genes together.
+#[derive(Clone, Debug)]
+pub struct InversionMutation {
+    /// Probability of inverting anything at all in a chromosome
+    chance: f32,
+}
+
+impl InversionMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+
+        Self { chance }
+    }
+}
+
+impl<G> MutationMethod<G> for InversionMutation
+where
+    G: Gene,
+{
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        if child.len() < 2 || !rng.gen_bool(self.chance as f64) {
+            return;
+        }
+
+        let genes = child.as_mut_slice();
+        let a = rng.gen_range(0..genes.len());
+        let b = rng.gen_range(0..genes.len());
+
+        genes[a.min(b)..=a.max(b)].reverse();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let original: Chromosome<usize> = (0..10).collect();
+        let mut child = original.clone();
+
+        InversionMutation::new(1.0).mutate(&mut rng, &mut child);
+
+        // Whatever has changed is a single, reversed run of the original
+        let changed: Vec<_> = (0..child.len())
+            .filter(|&idx| child[idx] != original[idx])
+            .collect();
+
+        assert!(!changed.is_empty());
+
+        let (start, end) = (changed[0], changed[changed.len() - 1]);
+
+        for idx in start..=end {
+            assert_eq!(child[idx], original[start + end - idx]);
+        }
+    }
+}
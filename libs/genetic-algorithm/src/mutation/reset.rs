@@ -0,0 +1,59 @@
+use crate::*;
+use rand::distributions::uniform::SampleUniform;
+
+/// Mutation for bounded genes (e.g. integers): replaces each gene, with
+/// given probability, with a random value from `min..=max`.
+#[derive(Clone, Debug)]
+pub struct ResetMutation<G> {
+    /// Probability of resetting a gene:
+    /// - 0.0 = no genes will be touched
+    /// - 1.0 = all genes will be reset
+    chance: f32,
+
+    min: G,
+    max: G,
+}
+
+impl<G> ResetMutation<G>
+where
+    G: PartialOrd,
+{
+    pub fn new(chance: f32, min: G, max: G) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(min <= max);
+
+        Self { chance, min, max }
+    }
+}
+
+impl<G> MutationMethod<G> for ResetMutation<G>
+where
+    G: Gene + PartialOrd + SampleUniform,
+{
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as f64) {
+                *gene = rng.gen_range(self.min.clone()..=self.max.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<i32> = (0..100).map(|_| 0).collect();
+
+        ResetMutation::new(1.0, -3, 3).mutate(&mut rng, &mut child);
+
+        assert!(child.iter().all(|gene| (-3..=3).contains(gene)));
+        assert!(child.iter().any(|&gene| gene == -3));
+        assert!(child.iter().any(|&gene| gene == 3));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::*;
+
+/// Mutation for permutations: swaps two random genes with each other, so
+/// that the chromosome remains a permutation.
+#[derive(Clone, Debug)]
+pub struct SwapMutation {
+    /// Probability of swapping anything at all in a chromosome
+    chance: f32,
+}
+
+impl SwapMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+
+        Self { chance }
+    }
+}
+
+impl<G> MutationMethod<G> for SwapMutation
+where
+    G: Gene,
+{
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        if child.len() < 2 || !rng.gen_bool(self.chance as f64) {
+            return;
+        }
+
+        let genes = child.as_mut_slice();
+        let a = rng.gen_range(0..genes.len());
+        let b = rng.gen_range(0..genes.len());
+
+        genes.swap(a, b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let original: Chromosome<usize> = (0..10).collect();
+        let mut child = original.clone();
+
+        SwapMutation::new(1.0).mutate(&mut rng, &mut child);
+
+        let moved = child
+            .iter()
+            .zip(original.iter())
+            .filter(|(c, o)| c != o)
+            .count();
+
+        assert_eq!(moved, 2);
+        assert_eq!(sorted(&child), sorted(&original));
+    }
+
+    fn sorted(chromosome: &Chromosome<usize>) -> Vec<usize> {
+        let mut genes = chromosome.as_slice().to_vec();
+        genes.sort();
+        genes
+    }
+}
@@ -0,0 +1,170 @@
+/// Number of behaviours `NoveltyArchive::new()` remembers at most
+pub const NOVELTY_ARCHIVE_CAPACITY: usize = 1000;
+
+/// Archive of behaviours seen so far, used to score individuals by how
+/// different they are from everything that came before them rather than
+/// by how good they are.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoveltyArchive {
+    /// How many nearest neighbours are averaged into an individual's
+    /// novelty
+    k: usize,
+
+    /// Minimum novelty an individual must reach for its behaviour to be
+    /// remembered in the archive
+    threshold: f32,
+
+    /// Maximum number of behaviours remembered; once it's reached, the
+    /// oldest behaviours get forgotten first
+    #[cfg_attr(feature = "serde", serde(default = "default_capacity"))]
+    capacity: usize,
+
+    behaviours: Vec<Vec<f32>>,
+}
+
+impl NoveltyArchive {
+    pub fn new(k: usize, threshold: f32) -> Self {
+        assert!(k > 0);
+        assert!(threshold >= 0.0);
+
+        Self {
+            k,
+            threshold,
+            capacity: NOVELTY_ARCHIVE_CAPACITY,
+            behaviours: Vec::new(),
+        }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0);
+
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn behaviours(&self) -> &[Vec<f32>] {
+        &self.behaviours
+    }
+
+    pub fn len(&self) -> usize {
+        self.behaviours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.behaviours.is_empty()
+    }
+
+    /// Returns novelty of each behaviour - the average distance to its `k`
+    /// nearest neighbours among both the rest of the population and the
+    /// archive - and then archives the behaviours that were novel enough.
+    pub fn evaluate(&mut self, behaviours: &[Vec<f32>]) -> Vec<f32> {
+        let scores: Vec<_> = behaviours
+            .iter()
+            .enumerate()
+            .map(|(idx, behaviour)| {
+                let mut distances: Vec<_> = behaviours
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_idx, _)| *other_idx != idx)
+                    .map(|(_, other)| other)
+                    .chain(&self.behaviours)
+                    .map(|other| distance(behaviour, other))
+                    .collect();
+
+                if distances.is_empty() {
+                    return 0.0;
+                }
+
+                distances.sort_by(f32::total_cmp);
+                distances.truncate(self.k);
+
+                distances.iter().sum::<f32>() / distances.len() as f32
+            })
+            .collect();
+
+        for (behaviour, &score) in behaviours.iter().zip(&scores) {
+            if score > self.threshold {
+                self.behaviours.push(behaviour.clone());
+            }
+        }
+
+        if self.behaviours.len() > self.capacity {
+            let excess = self.behaviours.len() - self.capacity;
+            self.behaviours.drain(..excess);
+        }
+
+        scores
+    }
+}
+
+#[cfg(feature = "serde")]
+fn default_capacity() -> usize {
+    NOVELTY_ARCHIVE_CAPACITY
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len());
+
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn scores_by_nearest_neighbours() {
+        let mut archive = NoveltyArchive::new(2, 100.0);
+
+        let actual = archive.evaluate(&[
+            vec![0.0, 0.0],
+            vec![1.0, 0.0],
+            vec![0.0, 2.0],
+            vec![10.0, 0.0],
+        ]);
+
+        let expected = [
+            (1.0 + 2.0) / 2.0,
+            (1.0 + 5.0f32.sqrt()) / 2.0,
+            (2.0 + 5.0f32.sqrt()) / 2.0,
+            (9.0 + 10.0) / 2.0,
+        ];
+
+        assert_relative_eq!(actual.as_slice(), expected.as_slice());
+        assert!(archive.is_empty());
+    }
+
+    #[test]
+    fn archives_novel_behaviours() {
+        let mut archive = NoveltyArchive::new(1, 5.0);
+
+        archive.evaluate(&[vec![0.0], vec![1.0], vec![10.0]]);
+        assert_eq!(archive.behaviours(), &[vec![10.0]]);
+
+        // Behaviour that's already been archived is no longer novel
+        let actual = archive.evaluate(&[vec![0.0], vec![10.0]]);
+
+        assert_relative_eq!(actual.as_slice(), [10.0, 0.0].as_slice());
+        assert_eq!(archive.len(), 2);
+    }
+
+    #[test]
+    fn forgets_oldest_behaviours() {
+        let mut archive = NoveltyArchive::new(1, 0.5).with_capacity(2);
+
+        archive.evaluate(&[vec![0.0], vec![10.0]]);
+        archive.evaluate(&[vec![20.0]]);
+
+        assert_eq!(archive.behaviours(), &[vec![10.0], vec![20.0]]);
+    }
+}
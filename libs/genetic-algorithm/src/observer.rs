@@ -0,0 +1,264 @@
+use crate::*;
+use std::sync::{Arc, Mutex};
+
+/// Callbacks invoked while evolving, so that tools can log lineage, gather
+/// statistics or report progress without having to modify the algorithm.
+///
+/// All the callbacks do nothing by default. Parents are given as indices
+/// into the population being evolved, and children as indices into the new
+/// population.
+///
+/// With the `parallel` feature, breeding callbacks (from `selected()` to
+/// `created()`) get invoked from many threads at once, in no particular
+/// order - that's why they take `&self`.
+pub trait Observer<G = f32>: MaybeSync {
+    fn generation_started(&self, _generation: usize, _population: usize) {}
+
+    /// Called each time a parent gets selected; `fitness` is what the
+    /// selection went by (i.e. after penalties, scaling and sharing).
+    fn selected(&self, _parent: usize, _fitness: f32) {}
+
+    fn crossed_over(&self, _parent_a: usize, _parent_b: usize, _child: &Chromosome<G>) {}
+
+    fn mutated(&self, _before: &Chromosome<G>, _after: &Chromosome<G>) {}
+
+    /// Called once the child is ready (after it's been repaired, if
+    /// there's a repair method).
+    fn created(&self, _child: usize, _parents: [usize; 2], _chromosome: &Chromosome<G>) {}
+
+    /// Called when a child gets replaced with a random immigrant (see
+    /// `GeneticAlgorithm::with_immigrants()` and `with_restart()`).
+    fn immigrated(&self, _child: usize, _chromosome: &Chromosome<G>) {}
+
+    /// Called after the population's been restarted.
+    fn restarted(&self, _generation: usize) {}
+
+    fn generation_finished(&self, _generation: usize, _statistics: &Statistics) {}
+}
+
+/// Lets the caller keep a handle to the observer it's passed to the
+/// algorithm, to read whatever the observer has gathered.
+impl<G, O> Observer<G> for Arc<O>
+where
+    O: Observer<G> + ?Sized,
+    Arc<O>: MaybeSync,
+{
+    fn generation_started(&self, generation: usize, population: usize) {
+        (**self).generation_started(generation, population)
+    }
+
+    fn selected(&self, parent: usize, fitness: f32) {
+        (**self).selected(parent, fitness)
+    }
+
+    fn crossed_over(&self, parent_a: usize, parent_b: usize, child: &Chromosome<G>) {
+        (**self).crossed_over(parent_a, parent_b, child)
+    }
+
+    fn mutated(&self, before: &Chromosome<G>, after: &Chromosome<G>) {
+        (**self).mutated(before, after)
+    }
+
+    fn created(&self, child: usize, parents: [usize; 2], chromosome: &Chromosome<G>) {
+        (**self).created(child, parents, chromosome)
+    }
+
+    fn immigrated(&self, child: usize, chromosome: &Chromosome<G>) {
+        (**self).immigrated(child, chromosome)
+    }
+
+    fn restarted(&self, generation: usize) {
+        (**self).restarted(generation)
+    }
+
+    fn generation_finished(&self, generation: usize, statistics: &Statistics) {
+        (**self).generation_finished(generation, statistics)
+    }
+}
+
+/// Observer noting down who the parents of each child bred in the latest
+/// generation were - the building block for tracking lineage.
+#[derive(Debug, Default)]
+pub struct Parentage {
+    parents: Mutex<Vec<Option<[usize; 2]>>>,
+}
+
+impl Parentage {
+    /// Returns indices (into the previous population) of given child's
+    /// parents, or `None` if the child hasn't been bred.
+    pub fn parents(&self, child: usize) -> Option<[usize; 2]> {
+        self.parents.lock().unwrap().get(child).copied().flatten()
+    }
+}
+
+impl<G> Observer<G> for Parentage {
+    fn generation_started(&self, _: usize, population: usize) {
+        let mut parents = self.parents.lock().unwrap();
+
+        parents.clear();
+        parents.resize(population, None);
+    }
+
+    fn created(&self, child: usize, parents: [usize; 2], _: &Chromosome<G>) {
+        let mut all = self.parents.lock().unwrap();
+
+        if all.len() <= child {
+            all.resize(child + 1, None);
+        }
+
+        all[child] = Some(parents);
+    }
+
+    fn immigrated(&self, child: usize, _: &Chromosome<G>) {
+        if let Some(parents) = self.parents.lock().unwrap().get_mut(child) {
+            *parents = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+        children: Mutex<Vec<(usize, [usize; 2])>>,
+    }
+
+    impl Recorder {
+        fn record(&self, event: &str) {
+            self.events.lock().unwrap().push(event.to_string());
+        }
+
+        fn count(&self, event: &str) -> usize {
+            self.events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|e| *e == event)
+                .count()
+        }
+    }
+
+    impl Observer for Recorder {
+        fn generation_started(&self, generation: usize, population: usize) {
+            self.record(&format!("started {} {}", generation, population));
+        }
+
+        fn selected(&self, _: usize, _: f32) {
+            self.record("selected");
+        }
+
+        fn crossed_over(&self, _: usize, _: usize, _: &Chromosome) {
+            self.record("crossed over");
+        }
+
+        fn mutated(&self, _: &Chromosome, _: &Chromosome) {
+            self.record("mutated");
+        }
+
+        fn created(&self, child: usize, parents: [usize; 2], _: &Chromosome) {
+            self.children.lock().unwrap().push((child, parents));
+        }
+
+        fn generation_finished(&self, generation: usize, statistics: &Statistics) {
+            self.record(&format!("finished {} {}", generation, statistics.species()));
+        }
+    }
+
+    fn individual(genes: &[f32]) -> TestIndividual {
+        TestIndividual::create(genes.iter().cloned().collect())
+    }
+
+    #[test]
+    fn test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let recorder = Arc::new(Recorder::default());
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        )
+        .with_speciation(Speciation::new(2.0))
+        .with_observer(recorder.clone());
+
+        // Species #0 is the only one with any fitness, so all the children
+        // must come from it
+        let population = vec![
+            individual(&[1.0, 1.0, 1.0]),
+            individual(&[8.0, -8.0, 0.0]),
+            individual(&[1.0, 2.0, 1.0]),
+            individual(&[8.0, -9.0, 1.0]),
+        ];
+
+        Optimizer::evolve(&mut ga, &mut rng, &population);
+        Optimizer::evolve(&mut ga, &mut rng, &population);
+
+        assert_eq!(recorder.count("started 0 4"), 1);
+        assert_eq!(recorder.count("finished 0 2"), 1);
+        assert_eq!(recorder.count("started 1 4"), 1);
+        assert_eq!(recorder.count("selected"), 16);
+        assert_eq!(recorder.count("crossed over"), 8);
+        assert_eq!(recorder.count("mutated"), 8);
+
+        let mut children = recorder.children.lock().unwrap().clone();
+        children.sort();
+
+        assert_eq!(children.len(), 8);
+
+        for (idx, (child, parents)) in children.iter().enumerate() {
+            assert_eq!(*child, idx / 2);
+
+            for parent in parents {
+                assert!([0, 2].contains(parent));
+            }
+        }
+    }
+
+    #[test]
+    fn parentage() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parentage = Arc::new(Parentage::default());
+
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        )
+        .with_observer(parentage.clone());
+
+        // Only the last two individuals have any fitness, so only they can
+        // become parents
+        let population = vec![
+            individual(&[0.0, 0.0]),
+            individual(&[0.0, 0.0]),
+            individual(&[1.0, 0.0]),
+            individual(&[0.0, 2.0]),
+        ];
+
+        let (children, _) = ga.evolve(&mut rng, &population);
+
+        for (idx, child) in children.iter().enumerate() {
+            let [a, b] = parentage.parents(idx).unwrap();
+
+            assert!(a >= 2 && b >= 2);
+
+            // (no mutation, so each gene comes from either of the parents)
+            for (gene, (a, b)) in child.chromosome().iter().zip(
+                population[a]
+                    .chromosome()
+                    .iter()
+                    .zip(population[b].chromosome().iter()),
+            ) {
+                assert!(gene == a || gene == b);
+            }
+        }
+
+        assert_eq!(parentage.parents(4), None);
+    }
+}
@@ -0,0 +1,115 @@
+mod cma_es;
+mod differential_evolution;
+
+pub use self::cma_es::*;
+pub use self::differential_evolution::*;
+use crate::*;
+
+/// Anything that can turn an evaluated population into the next one.
+///
+/// Optimizers are allowed to keep state between generations (e.g. CMA-ES
+/// remembers its search distribution), which is why `evolve()` takes
+/// `&mut self`.
+pub trait Optimizer<I>
+where
+    I: Individual,
+{
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics);
+}
+
+impl<S, I> Optimizer<I> for GeneticAlgorithm<S>
+where
+    S: SelectionMethod,
+    I: Individual,
+{
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics) {
+        let result = GeneticAlgorithm::evolve(self, rng, population);
+
+        self.generation += 1;
+        result
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod benchmarks {
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// Sphere function, negated so that its maximum (0.0) lies at the origin
+    #[derive(Clone, Debug)]
+    pub struct Sphere(Chromosome);
+
+    impl Individual for Sphere {
+        fn create(chromosome: Chromosome) -> Self {
+            Self(chromosome)
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.0
+        }
+
+        fn fitness(&self) -> f32 {
+            -self.0.iter().map(|x| x * x).sum::<f32>()
+        }
+    }
+
+    /// Rastrigin function, negated so that its maximum (0.0) lies at the
+    /// origin, surrounded by lots of local maxima
+    #[derive(Clone, Debug)]
+    pub struct Rastrigin(Chromosome);
+
+    impl Individual for Rastrigin {
+        fn create(chromosome: Chromosome) -> Self {
+            Self(chromosome)
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.0
+        }
+
+        fn fitness(&self) -> f32 {
+            let n = self.0.len() as f32;
+
+            -(10.0 * n
+                + self
+                    .0
+                    .iter()
+                    .map(|x| x * x - 10.0 * (2.0 * PI * x).cos())
+                    .sum::<f32>())
+        }
+    }
+
+    /// Runs `optimizer` for given number of generations and returns the
+    /// best fitness it has found
+    pub fn optimize<I>(
+        rng: &mut dyn RngCore,
+        optimizer: &mut dyn Optimizer<I>,
+        population_size: usize,
+        dimensions: usize,
+        generations: usize,
+    ) -> f32
+    where
+        I: Individual,
+    {
+        let mut population: Vec<I> = (0..population_size)
+            .map(|_| {
+                I::create(
+                    (0..dimensions)
+                        .map(|_| rng.gen_range(-5.12..=5.12))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let mut best = f32::MIN;
+
+        for _ in 0..generations {
+            let stats;
+
+            (population, stats) = optimizer.evolve(rng, &population);
+            best = best.max(stats.max_fitness());
+        }
+
+        best
+    }
+}
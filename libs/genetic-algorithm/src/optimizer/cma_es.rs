@@ -0,0 +1,336 @@
+use crate::*;
+use rand_distr::StandardNormal;
+
+/// Covariance Matrix Adaptation Evolution Strategy.
+///
+/// Instead of keeping a population around, CMA-ES keeps a multivariate
+/// normal distribution: each call to `evolve()` receives individuals
+/// sampled by the previous call (now evaluated), moves the distribution
+/// towards the better half of them, and samples a new population.
+///
+/// All the bookkeeping happens in `f64`, genes are converted from / to
+/// `f32` only when talking to the outside world.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CmaEs {
+    /// Initial step size, i.e. standard deviation of the very first
+    /// distribution
+    sigma0: f64,
+
+    state: Option<CmaEsState>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct CmaEsState {
+    mean: Vec<f64>,
+    sigma: f64,
+
+    /// Covariance matrix, row-major
+    cov: Vec<f64>,
+
+    /// Lower-triangular Cholesky factor of `cov`, row-major
+    chol: Vec<f64>,
+
+    /// Evolution path of the covariance matrix
+    pc: Vec<f64>,
+
+    /// Evolution path of the step size
+    ps: Vec<f64>,
+
+    generation: usize,
+}
+
+impl CmaEs {
+    pub fn new(sigma0: f32) -> Self {
+        assert!(sigma0 > 0.0);
+
+        Self {
+            sigma0: sigma0 as f64,
+            state: None,
+        }
+    }
+
+    /// Current step size, if the distribution has been initialized already
+    pub fn sigma(&self) -> Option<f32> {
+        self.state.as_ref().map(|state| state.sigma as f32)
+    }
+
+    fn tell<I>(&mut self, population: &[I])
+    where
+        I: Individual,
+    {
+        let n = population[0].chromosome().len();
+        let lambda = population.len();
+        let mu = lambda / 2;
+
+        let state = self.state.get_or_insert_with(|| {
+            // The very first distribution is an isotropic one, centered
+            // around whatever the caller started with
+            let mut mean = vec![0.0; n];
+
+            for individual in population {
+                for (m, &x) in mean.iter_mut().zip(individual.chromosome().iter()) {
+                    *m += x as f64 / lambda as f64;
+                }
+            }
+
+            CmaEsState {
+                mean,
+                sigma: self.sigma0,
+                cov: identity(n),
+                chol: identity(n),
+                pc: vec![0.0; n],
+                ps: vec![0.0; n],
+                generation: 0,
+            }
+        });
+
+        assert_eq!(state.mean.len(), n, "got chromosomes of different length");
+
+        // Recombination weights
+        let weights: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+
+        let weights_sum: f64 = weights.iter().sum();
+        let weights: Vec<f64> = weights.iter().map(|w| w / weights_sum).collect();
+        let mueff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        // Strategy parameters, as suggested by Hansen's tutorial
+        let nf = n as f64;
+        let cc = (4.0 + mueff / nf) / (nf + 4.0 + 2.0 * mueff / nf);
+        let cs = (mueff + 2.0) / (nf + mueff + 5.0);
+        let c1 = 2.0 / ((nf + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((nf + 2.0).powi(2) + mueff));
+        let damps = 1.0 + 2.0 * (((mueff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+
+        // Best individuals first
+        let mut ranked: Vec<&I> = population.iter().collect();
+        ranked.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+
+        // Steps taken from the old mean, in units of sigma
+        let steps: Vec<Vec<f64>> = ranked[..mu]
+            .iter()
+            .map(|individual| {
+                individual
+                    .chromosome()
+                    .iter()
+                    .zip(&state.mean)
+                    .map(|(&x, m)| (x as f64 - m) / state.sigma)
+                    .collect()
+            })
+            .collect();
+
+        let mut step = vec![0.0; n];
+
+        for (w, s) in weights.iter().zip(&steps) {
+            for (acc, s) in step.iter_mut().zip(s) {
+                *acc += w * s;
+            }
+        }
+
+        for (m, s) in state.mean.iter_mut().zip(&step) {
+            *m += state.sigma * s;
+        }
+
+        // Step-size path lives in the whitened space: `L^-1 * step`
+        let whitened = solve_lower(&state.chol, &step, n);
+        let ps_coeff = (cs * (2.0 - cs) * mueff).sqrt();
+
+        for (ps, z) in state.ps.iter_mut().zip(&whitened) {
+            *ps = (1.0 - cs) * *ps + ps_coeff * z;
+        }
+
+        let ps_norm = norm(&state.ps);
+        state.generation += 1;
+
+        let hsig = ps_norm / (1.0 - (1.0 - cs).powi(2 * state.generation as i32)).sqrt() / chi_n
+            < 1.4 + 2.0 / (nf + 1.0);
+
+        let hsig = if hsig { 1.0 } else { 0.0 };
+        let pc_coeff = (cc * (2.0 - cc) * mueff).sqrt();
+
+        for (pc, s) in state.pc.iter_mut().zip(&step) {
+            *pc = (1.0 - cc) * *pc + hsig * pc_coeff * s;
+        }
+
+        for row in 0..n {
+            for col in 0..=row {
+                let rank_one = state.pc[row] * state.pc[col]
+                    + (1.0 - hsig) * cc * (2.0 - cc) * state.cov[row * n + col];
+
+                let rank_mu: f64 = weights
+                    .iter()
+                    .zip(&steps)
+                    .map(|(w, s)| w * s[row] * s[col])
+                    .sum();
+
+                let value =
+                    (1.0 - c1 - cmu) * state.cov[row * n + col] + c1 * rank_one + cmu * rank_mu;
+
+                state.cov[row * n + col] = value;
+                state.cov[col * n + row] = value;
+            }
+        }
+
+        state.sigma *= ((cs / damps) * (ps_norm / chi_n - 1.0)).exp();
+
+        state.chol = cholesky(&state.cov, n).unwrap_or_else(|| {
+            // Numerical trouble; start over with an isotropic distribution,
+            // keeping the mean and step size we've got so far
+            state.cov = identity(n);
+            state.pc = vec![0.0; n];
+            state.ps = vec![0.0; n];
+
+            identity(n)
+        });
+    }
+
+    fn ask<I>(&self, rng: &mut dyn RngCore, count: usize) -> Vec<I>
+    where
+        I: Individual,
+    {
+        let state = self.state.as_ref().unwrap();
+        let n = state.mean.len();
+
+        (0..count)
+            .map(|_| {
+                let z: Vec<f64> = (0..n).map(|_| rng.sample(StandardNormal)).collect();
+
+                let chromosome = (0..n)
+                    .map(|row| {
+                        let y: f64 = (0..=row)
+                            .map(|col| state.chol[row * n + col] * z[col])
+                            .sum();
+
+                        (state.mean[row] + state.sigma * y) as f32
+                    })
+                    .collect();
+
+                I::create(chromosome)
+            })
+            .collect()
+    }
+}
+
+impl<I> Optimizer<I> for CmaEs
+where
+    I: Individual,
+{
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics) {
+        assert!(
+            population.len() >= 2,
+            "CMA-ES needs at least two individuals"
+        );
+
+        self.tell(population);
+
+        (
+            self.ask(rng, population.len()),
+            Statistics::new(population, 1),
+        )
+    }
+}
+
+fn identity(n: usize) -> Vec<f64> {
+    (0..n * n)
+        .map(|idx| if idx / n == idx % n { 1.0 } else { 0.0 })
+        .collect()
+}
+
+fn norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Returns `L` such that `L * L^T = matrix`, or `None` if `matrix` is not
+/// (numerically) positive-definite
+fn cholesky(matrix: &[f64], n: usize) -> Option<Vec<f64>> {
+    let mut l = vec![0.0; n * n];
+
+    for row in 0..n {
+        for col in 0..=row {
+            let sum: f64 = (0..col).map(|k| l[row * n + k] * l[col * n + k]).sum();
+
+            if row == col {
+                let diag = matrix[row * n + row] - sum;
+
+                if diag <= 0.0 || !diag.is_finite() {
+                    return None;
+                }
+
+                l[row * n + col] = diag.sqrt();
+            } else {
+                l[row * n + col] = (matrix[row * n + col] - sum) / l[col * n + col];
+            }
+        }
+    }
+
+    Some(l)
+}
+
+/// Solves `L * x = b` for lower-triangular `L`
+fn solve_lower(l: &[f64], b: &[f64], n: usize) -> Vec<f64> {
+    let mut x = vec![0.0; n];
+
+    for row in 0..n {
+        let sum: f64 = (0..row).map(|col| l[row * n + col] * x[col]).sum();
+        x[row] = (b[row] - sum) / l[row * n + row];
+    }
+
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizer::benchmarks::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn run<I>(population_size: usize, generations: usize) -> f32
+    where
+        I: Individual,
+    {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut cma_es = CmaEs::new(2.0);
+
+        optimize::<I>(&mut rng, &mut cma_es, population_size, 5, generations)
+    }
+
+    #[test]
+    fn cholesky() {
+        let matrix = [4.0, 2.0, 2.0, 5.0];
+        let actual = super::cholesky(&matrix, 2).unwrap();
+
+        assert_relative_eq!(actual.as_slice(), [2.0, 0.0, 1.0, 2.0].as_slice());
+        assert!(super::cholesky(&[1.0, 2.0, 2.0, 1.0], 2).is_none());
+    }
+
+    #[test]
+    fn solve_lower() {
+        let l = [2.0, 0.0, 1.0, 2.0];
+        let actual = super::solve_lower(&l, &[4.0, 6.0], 2);
+
+        assert_relative_eq!(actual.as_slice(), [2.0, 2.0].as_slice());
+    }
+
+    #[test]
+    fn sphere() {
+        assert!(run::<Sphere>(10, 150) > -1e-6);
+    }
+
+    #[test]
+    fn rastrigin() {
+        // Rastrigin is full of local maxima, which CMA-ES gets out of only
+        // given a large enough population
+        assert!(run::<Rastrigin>(100, 300) > -1e-2);
+    }
+
+    #[test]
+    fn is_reproducible() {
+        assert_eq!(run::<Rastrigin>(10, 20), run::<Rastrigin>(10, 20));
+    }
+}
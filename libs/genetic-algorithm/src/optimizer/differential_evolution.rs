@@ -0,0 +1,177 @@
+use crate::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum DifferentialStrategy {
+    /// Mutant vector is built around a random member of the population
+    Rand1Bin,
+
+    /// Mutant vector is built around the best member of the population
+    Best1Bin,
+}
+
+/// Differential evolution.
+///
+/// Each call to `evolve()` receives trial vectors produced by the previous
+/// call (now evaluated), keeps each of them only if it's at least as fit as
+/// the target it was created for, and then creates a new trial vector per
+/// target.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DifferentialEvolution {
+    strategy: DifferentialStrategy,
+
+    /// Differential weight (usually `F`), scaling difference between the
+    /// two random vectors added to the base vector
+    weight: f32,
+
+    /// Crossover probability (usually `CR`):
+    /// - 0.0 = trial vector takes a single gene from the mutant vector
+    /// - 1.0 = trial vector is the mutant vector
+    crossover: f32,
+
+    targets: Vec<(Chromosome, f32)>,
+}
+
+impl DifferentialEvolution {
+    pub fn new(strategy: DifferentialStrategy, weight: f32, crossover: f32) -> Self {
+        assert!((0.0..=2.0).contains(&weight));
+        assert!((0.0..=1.0).contains(&crossover));
+
+        Self {
+            strategy,
+            weight,
+            crossover,
+            targets: Vec::new(),
+        }
+    }
+
+    fn select<I>(&mut self, population: &[I])
+    where
+        I: Individual,
+    {
+        if self.targets.len() != population.len() {
+            self.targets = population
+                .iter()
+                .map(|individual| (individual.chromosome().clone(), individual.fitness()))
+                .collect();
+
+            return;
+        }
+
+        for (target, trial) in self.targets.iter_mut().zip(population) {
+            if trial.fitness() >= target.1 {
+                *target = (trial.chromosome().clone(), trial.fitness());
+            }
+        }
+    }
+
+    fn trial(&self, rng: &mut dyn RngCore, idx: usize, best: usize) -> Chromosome {
+        let target = &self.targets[idx].0;
+
+        // Picks three distinct vectors, all different from the target
+        let mut picked = [idx; 3];
+
+        for n in 0..picked.len() {
+            picked[n] = loop {
+                let candidate = rng.gen_range(0..self.targets.len());
+
+                if candidate != idx && !picked[..n].contains(&candidate) {
+                    break candidate;
+                }
+            };
+        }
+
+        let base = match self.strategy {
+            DifferentialStrategy::Rand1Bin => &self.targets[picked[0]].0,
+            DifferentialStrategy::Best1Bin => &self.targets[best].0,
+        };
+
+        let a = &self.targets[picked[1]].0;
+        let b = &self.targets[picked[2]].0;
+
+        // Gene that's always taken from the mutant vector, so that the trial
+        // vector never ends up being a copy of the target
+        let forced = rng.gen_range(0..target.len());
+
+        (0..target.len())
+            .map(|gene| {
+                if gene == forced || rng.gen_bool(self.crossover as f64) {
+                    base[gene] + self.weight * (a[gene] - b[gene])
+                } else {
+                    target[gene]
+                }
+            })
+            .collect()
+    }
+}
+
+impl<I> Optimizer<I> for DifferentialEvolution
+where
+    I: Individual,
+{
+    fn evolve(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics) {
+        assert!(
+            population.len() >= 4,
+            "differential evolution needs at least four individuals"
+        );
+
+        self.select(population);
+
+        let best = self
+            .targets
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.1.total_cmp(&b.1))
+            .map(|(idx, _)| idx)
+            .unwrap();
+
+        let trials = (0..self.targets.len())
+            .map(|idx| I::create(self.trial(rng, idx, best)))
+            .collect();
+
+        (trials, Statistics::new(population, 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimizer::benchmarks::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn run<I>(strategy: DifferentialStrategy, generations: usize) -> f32
+    where
+        I: Individual,
+    {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut de = DifferentialEvolution::new(strategy, 0.5, 0.9);
+
+        optimize::<I>(&mut rng, &mut de, 40, 5, generations)
+    }
+
+    #[test]
+    fn rand_1_bin_on_sphere() {
+        assert!(run::<Sphere>(DifferentialStrategy::Rand1Bin, 300) > -1e-4);
+    }
+
+    #[test]
+    fn best_1_bin_on_sphere() {
+        assert!(run::<Sphere>(DifferentialStrategy::Best1Bin, 150) > -1e-4);
+    }
+
+    #[test]
+    fn rand_1_bin_on_rastrigin() {
+        assert!(run::<Rastrigin>(DifferentialStrategy::Rand1Bin, 500) > -1e-2);
+    }
+
+    #[test]
+    fn is_reproducible() {
+        let a = run::<Rastrigin>(DifferentialStrategy::Rand1Bin, 20);
+        let b = run::<Rastrigin>(DifferentialStrategy::Rand1Bin, 20);
+
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,17 @@
+/// `Send + Sync` when the `parallel` feature is enabled, nothing otherwise.
+///
+/// Breeding children on many threads requires sharing selection, crossover
+/// and mutation methods (and the population itself) between threads, but
+/// there's no reason to impose that on single-threaded builds (e.g. WASM).
+#[cfg(feature = "parallel")]
+pub trait MaybeSync: Send + Sync {}
+
+#[cfg(feature = "parallel")]
+impl<T> MaybeSync for T where T: Send + Sync {}
+
+/// `Send + Sync` when the `parallel` feature is enabled, nothing otherwise.
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> MaybeSync for T {}
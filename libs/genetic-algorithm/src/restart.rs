@@ -0,0 +1,313 @@
+use crate::*;
+
+/// Creates random chromosomes - used for immigrants and restarts (see
+/// `GeneticAlgorithm::with_generator()`).
+///
+/// Closures taking an rng and returning a chromosome are generators, too.
+pub trait Generator<G = f32>: MaybeSync {
+    fn generate(&self, rng: &mut dyn RngCore) -> Chromosome<G>;
+}
+
+impl<G, F> Generator<G> for F
+where
+    F: Fn(&mut dyn RngCore) -> Chromosome<G> + MaybeSync,
+{
+    fn generate(&self, rng: &mut dyn RngCore) -> Chromosome<G> {
+        self(rng)
+    }
+}
+
+/// Replaces (a part of) the population with random individuals once
+/// evolution gets stuck.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Restart {
+    /// Number of generations without any improvement of the best fitness
+    /// after which the population gets restarted
+    pub stagnation: usize,
+
+    /// Fraction of the population (elites aside) that gets replaced - 1.0
+    /// for a full restart
+    pub fraction: f32,
+
+    /// Number of the fittest individuals that survive the restart
+    pub elites: usize,
+}
+
+impl Restart {
+    pub fn is_valid(&self) -> bool {
+        self.stagnation > 0 && self.fraction > 0.0 && self.fraction <= 1.0
+    }
+}
+
+/// How long the best fitness has been stuck at the same level.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plateau {
+    /// Best fitness seen so far
+    pub best_fitness: Option<f32>,
+
+    /// Number of generations since `best_fitness` has last improved (or
+    /// since the last restart)
+    pub generations: usize,
+}
+
+impl Plateau {
+    /// Notes down best fitness of the latest generation; returns for how
+    /// many generations it's been stuck.
+    pub fn update(&mut self, fitness: f32) -> usize {
+        if self.best_fitness.map_or(true, |best| fitness > best) {
+            self.best_fitness = Some(fitness);
+            self.generations = 0;
+        } else {
+            self.generations += 1;
+        }
+
+        self.generations
+    }
+}
+
+impl<S, G> GeneticAlgorithm<S, G>
+where
+    S: SelectionMethod,
+    G: Gene,
+{
+    /// Replaces a fraction of each new generation with random immigrants,
+    /// which keeps fresh genes flowing in; requires a generator.
+    pub fn with_immigrants(mut self, fraction: f32) -> Self {
+        assert!((0.0..=1.0).contains(&fraction));
+
+        self.immigrants = fraction;
+        self
+    }
+
+    /// Restarts the population once it's stagnated; requires a generator.
+    pub fn with_restart(mut self, restart: Restart) -> Self {
+        assert!(restart.is_valid());
+
+        self.restart = Some(restart);
+        self
+    }
+
+    /// Sets the generator that immigrants and restarted individuals get
+    /// created with.
+    pub fn with_generator(mut self, generator: impl Generator<G> + 'static) -> Self {
+        self.generator = Some(Box::new(generator));
+        self
+    }
+
+    /// Makes the algorithm pick up the stagnation count from where it's
+    /// been left off, e.g. after it's been restored from a save.
+    pub fn with_plateau(mut self, plateau: Plateau) -> Self {
+        self.plateau = Mutex::new(plateau);
+        self
+    }
+
+    pub fn plateau(&self) -> Plateau {
+        *self.plateau.lock().unwrap()
+    }
+
+    /// Replaces random children with immigrants.
+    pub(crate) fn immigrate<I>(&self, rng: &mut dyn RngCore, children: &mut [I])
+    where
+        I: Individual<G>,
+    {
+        let count = (self.immigrants * children.len() as f32).round() as usize;
+
+        if count == 0 {
+            return;
+        }
+
+        for child in rand::seq::index::sample(rng, children.len(), count) {
+            children[child] = self.immigrant(rng, child);
+        }
+    }
+
+    /// Restarts the population if it's stagnated; `population` is the one
+    /// that `children` have been bred from.
+    pub(crate) fn restart<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        children: &mut [I],
+        statistics: &Statistics,
+    ) where
+        I: Individual<G>,
+    {
+        let Some(restart) = self.restart else {
+            return;
+        };
+
+        {
+            let mut plateau = self.plateau.lock().unwrap();
+
+            if plateau.update(statistics.max_fitness()) < restart.stagnation {
+                return;
+            }
+
+            plateau.generations = 0;
+        }
+
+        let mut ranked: Vec<_> = (0..population.len()).collect();
+
+        ranked.sort_by(|&a, &b| population[b].fitness().total_cmp(&population[a].fitness()));
+
+        let elites = restart.elites.min(ranked.len()).min(children.len());
+
+        for (child, &elite) in ranked[..elites].iter().enumerate() {
+            let chromosome = population[elite].chromosome().clone();
+
+            for observer in &self.observers {
+                observer.created(child, [elite, elite], &chromosome);
+            }
+
+            children[child] = I::create(chromosome);
+        }
+
+        let others = children.len() - elites;
+        let count = (restart.fraction * others as f32).round() as usize;
+
+        for child in rand::seq::index::sample(rng, others, count) {
+            children[elites + child] = self.immigrant(rng, elites + child);
+        }
+
+        for observer in &self.observers {
+            observer.restarted(self.generation);
+        }
+    }
+
+    fn immigrant<I>(&self, rng: &mut dyn RngCore, child: usize) -> I
+    where
+        I: Individual<G>,
+    {
+        let chromosome = self
+            .generator
+            .as_ref()
+            .expect("checked by evolve()")
+            .generate(rng);
+
+        for observer in &self.observers {
+            observer.immigrated(child, &chromosome);
+        }
+
+        I::create(chromosome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn generator(rng: &mut dyn RngCore) -> Chromosome {
+        (0..3).map(|_| rng.gen_range(100.0..200.0)).collect()
+    }
+
+    fn ga() -> GeneticAlgorithm<RouletteWheelSelection> {
+        GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        )
+        .with_generator(generator)
+    }
+
+    fn population() -> Vec<TestIndividual> {
+        (0..10)
+            .map(|n| TestIndividual::create([n as f32, 1.0, 2.0].into_iter().collect()))
+            .collect()
+    }
+
+    fn immigrants(population: &[TestIndividual]) -> usize {
+        population
+            .iter()
+            .filter(|individual| individual.fitness() >= 300.0)
+            .count()
+    }
+
+    #[test]
+    fn immigrants_flow_in() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let ga = ga().with_immigrants(0.3);
+
+        let (population, _) = ga.evolve(&mut rng, &population());
+
+        assert_eq!(population.len(), 10);
+        assert_eq!(immigrants(&population), 3);
+    }
+
+    #[test]
+    fn restarts_on_stagnation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let mut ga = ga().with_restart(Restart {
+            stagnation: 2,
+            fraction: 1.0,
+            elites: 2,
+        });
+
+        let population = population();
+        let mut restarts = Vec::new();
+
+        // Without mutation, the best fitness can't ever improve
+        for generation in 0..6 {
+            let (children, _) = Optimizer::evolve(&mut ga, &mut rng, &population);
+
+            if immigrants(&children) > 0 {
+                assert_eq!(immigrants(&children), 8);
+
+                // Elites survive at the front
+                assert_eq!(children[0], population[9]);
+                assert_eq!(children[1], population[8]);
+
+                restarts.push(generation);
+            }
+        }
+
+        assert_eq!(restarts, [2, 4]);
+        assert_eq!(
+            ga.plateau(),
+            Plateau {
+                best_fitness: Some(12.0),
+                generations: 1
+            }
+        );
+    }
+
+    #[test]
+    fn partial_restart() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = ga()
+            .with_restart(Restart {
+                stagnation: 1,
+                fraction: 0.5,
+                elites: 0,
+            })
+            .with_plateau(Plateau {
+                best_fitness: Some(100.0),
+                generations: 0,
+            });
+
+        // Plain `evolve()` restarts, too
+        let (children, _) = ga.evolve(&mut rng, &population());
+
+        assert_eq!(immigrants(&children), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "immigrants and restarts require a generator")]
+    fn requires_generator() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        )
+        .with_immigrants(0.1);
+
+        let _: (Vec<TestIndividual>, _) = ga.evolve(&mut rng, &population());
+    }
+}
@@ -0,0 +1,374 @@
+/// Transformation applied to fitness before selection, so that e.g. a
+/// single lucky individual can't take over the whole roulette wheel.
+///
+/// Scaled fitness is never negative, even if the raw one is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "kebab-case"))]
+pub enum Scaling {
+    /// Linear scaling (`a * fitness + b`) that keeps the average fitness
+    /// where it is, but makes the best individual exactly `multiplier`
+    /// times better than the average one (typically 1.2 - 2.0)
+    Linear { multiplier: f32 },
+
+    /// Sigma truncation: subtracts `average - c * deviation` from each
+    /// fitness, dropping whoever ends up below zero
+    SigmaTruncation { c: f32 },
+
+    /// Raises each fitness to given power - exponents above 1.0 increase
+    /// the selection pressure, the ones below 1.0 decrease it
+    Power { exponent: f32 },
+
+    /// `exp(fitness / temperature)`, with the temperature multiplied by
+    /// `cooling` each generation (down to `min_temperature`) - so that the
+    /// selection starts exploratory and then gets more and more greedy
+    Boltzmann {
+        temperature: f32,
+        cooling: f32,
+        min_temperature: f32,
+    },
+
+    /// Replaces fitness with the individual's rank: 1.0 for the worst one,
+    /// 2.0 for the second worst, etc. (ties share the average rank)
+    Rank,
+}
+
+impl Scaling {
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Self::Linear { multiplier } => multiplier >= 1.0,
+            Self::SigmaTruncation { c } => c > 0.0,
+            Self::Power { exponent } => exponent > 0.0,
+
+            Self::Boltzmann {
+                temperature,
+                cooling,
+                min_temperature,
+            } => {
+                min_temperature > 0.0
+                    && temperature >= min_temperature
+                    && cooling > 0.0
+                    && cooling <= 1.0
+            }
+
+            Self::Rank => true,
+        }
+    }
+
+    /// Boltzmann scaling's temperature at given generation (or 1.0 for
+    /// all the other kinds of scaling).
+    pub fn temperature(&self, generation: usize) -> f32 {
+        match *self {
+            Self::Boltzmann {
+                temperature,
+                cooling,
+                min_temperature,
+            } => (temperature * cooling.powi(generation.min(i32::MAX as usize) as i32))
+                .max(min_temperature),
+
+            _ => 1.0,
+        }
+    }
+
+    /// Transforms fitness of the whole population at once.
+    pub fn apply(&self, fitness: &mut [f32], generation: usize) {
+        if fitness.is_empty() {
+            return;
+        }
+
+        let len = fitness.len() as f32;
+        let avg = fitness.iter().sum::<f32>() / len;
+        let min = fitness.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = fitness.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+        match *self {
+            Self::Linear { multiplier } => {
+                // Linear scaling only makes sense for non-negative fitness,
+                // so a negative one gets shifted up to zero first
+                let shift = min.min(0.0);
+
+                for fitness in fitness.iter_mut() {
+                    *fitness -= shift;
+                }
+
+                let (avg, min, max) = (avg - shift, min - shift, max - shift);
+
+                // Everybody's equally good, so there's nothing to scale
+                if max - avg <= f32::EPSILON * avg.abs().max(1.0) {
+                    fitness.fill(1.0);
+                    return;
+                }
+
+                let mut a = (multiplier - 1.0) * avg / (max - avg);
+                let mut b = avg - a * avg;
+
+                // Keeping the best one `multiplier` times better would make
+                // the worst one negative - scale as much as we can instead
+                if a * min + b < 0.0 {
+                    a = avg / (avg - min);
+                    b = -a * min;
+                }
+
+                for fitness in fitness.iter_mut() {
+                    *fitness = (a * *fitness + b).max(0.0);
+                }
+            }
+
+            Self::SigmaTruncation { c } => {
+                let deviation =
+                    (fitness.iter().map(|f| (f - avg) * (f - avg)).sum::<f32>() / len).sqrt();
+
+                let offset = avg - c * deviation;
+
+                for fitness in fitness.iter_mut() {
+                    *fitness = (*fitness - offset).max(0.0);
+                }
+            }
+
+            Self::Power { exponent } => {
+                for fitness in fitness.iter_mut() {
+                    *fitness = fitness.max(0.0).powf(exponent);
+                }
+            }
+
+            Self::Boltzmann { .. } => {
+                let temperature = self.temperature(generation);
+
+                // (shifting by `max` doesn't change the proportions, but
+                // keeps `exp()` from overflowing)
+                for fitness in fitness.iter_mut() {
+                    *fitness = ((*fitness - max) / temperature).exp();
+                }
+            }
+
+            Self::Rank => {
+                let mut order: Vec<_> = (0..fitness.len()).collect();
+                order.sort_by(|&a, &b| fitness[a].total_cmp(&fitness[b]));
+
+                let mut ranks = vec![0.0; fitness.len()];
+                let mut from = 0;
+
+                while from < order.len() {
+                    let mut to = from + 1;
+
+                    while to < order.len() && fitness[order[to]] == fitness[order[from]] {
+                        to += 1;
+                    }
+
+                    // Ranks `from + 1 ..= to`, averaged
+                    let rank = (from + 1 + to) as f32 / 2.0;
+
+                    for &idx in &order[from..to] {
+                        ranks[idx] = rank;
+                    }
+
+                    from = to;
+                }
+
+                fitness.copy_from_slice(&ranks);
+            }
+        }
+
+        // Nobody's left with any chance of being selected, so everybody
+        // gets the same one instead
+        if fitness.iter().sum::<f32>() <= 0.0 {
+            fitness.fill(1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::BTreeMap;
+
+    fn scaled(scaling: Scaling, fitness: &[f32]) -> Vec<f32> {
+        let mut fitness = fitness.to_vec();
+        scaling.apply(&mut fitness, 0);
+        fitness
+    }
+
+    /// Spins the roulette 1000 times over a population with given raw
+    /// fitness, scaled - returns how many times each individual won.
+    fn histogram(scaling: Scaling) -> BTreeMap<i32, usize> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let population = vec![
+            TestIndividual::new(2.0),
+            TestIndividual::new(1.0),
+            TestIndividual::new(4.0),
+            TestIndividual::new(3.0),
+        ];
+
+        let mut fitness: Vec<_> = population.iter().map(|i| i.fitness()).collect();
+        scaling.apply(&mut fitness, 0);
+
+        let population: Vec<_> = population
+            .iter()
+            .zip(fitness)
+            .map(|(individual, fitness)| Shared::new(individual, fitness))
+            .collect();
+
+        (0..1000)
+            .map(|_| RouletteWheelSelection.select(&mut rng, &population))
+            .fold(BTreeMap::default(), |mut histogram, individual| {
+                *histogram
+                    .entry(individual.individual.fitness() as i32)
+                    .or_default() += 1;
+                histogram
+            })
+    }
+
+    #[test]
+    fn linear() {
+        // Average stays at 2.5, while the best one goes to 1.5 * 2.5
+        let actual = scaled(Scaling::Linear { multiplier: 1.5 }, &[2.0, 1.0, 4.0, 3.0]);
+
+        approx::assert_relative_eq!(
+            actual.as_slice(),
+            [2.5 - 5.0 / 12.0, 1.25, 3.75, 2.5 + 5.0 / 12.0].as_slice(),
+            epsilon = 1e-6
+        );
+
+        // Minimum would go below zero, so it's pinned at zero instead
+        let actual = scaled(Scaling::Linear { multiplier: 3.0 }, &[2.0, 1.0, 4.0, 3.0]);
+
+        approx::assert_relative_eq!(
+            actual.as_slice(),
+            [5.0 / 3.0, 0.0, 5.0, 10.0 / 3.0].as_slice(),
+            epsilon = 1e-6
+        );
+
+        // Negative fitness gets shifted to [0.0, 2.0, 1.0] first
+        let actual = scaled(Scaling::Linear { multiplier: 1.5 }, &[-3.0, -1.0, -2.0]);
+
+        approx::assert_relative_eq!(
+            actual.as_slice(),
+            [0.5, 1.5, 1.0].as_slice(),
+            epsilon = 1e-6
+        );
+
+        let actual = histogram(Scaling::Linear { multiplier: 1.5 });
+
+        let expected = maplit::btreemap! {
+            // fitness => how many times this fitness has been chosen
+            1 => 116,
+            2 => 211,
+            3 => 274,
+            4 => 399,
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn sigma_truncation() {
+        // Average is 2.5 and deviation is 1.0, so 1.5 gets subtracted
+        let actual = scaled(Scaling::SigmaTruncation { c: 1.0 }, &[1.5, 1.5, 3.5, 3.5]);
+        approx::assert_relative_eq!(actual.as_slice(), [0.0, 0.0, 2.0, 2.0].as_slice());
+
+        // Nobody stands out, so nobody gets truncated either
+        let actual = scaled(Scaling::SigmaTruncation { c: 1.0 }, &[2.0, 2.0, 2.0]);
+        assert_eq!(actual, [1.0, 1.0, 1.0]);
+
+        let actual = histogram(Scaling::SigmaTruncation { c: 1.0 });
+
+        // The worst one ends up below the cut-off, so it never gets chosen
+        let expected = maplit::btreemap! {
+            2 => 137,
+            3 => 318,
+            4 => 545,
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn power() {
+        let actual = scaled(Scaling::Power { exponent: 2.0 }, &[2.0, -1.0, 4.0, 3.0]);
+        approx::assert_relative_eq!(actual.as_slice(), [4.0, 0.0, 16.0, 9.0].as_slice());
+
+        let actual = histogram(Scaling::Power { exponent: 2.0 });
+
+        let expected = maplit::btreemap! {
+            1 => 22,
+            2 => 142,
+            3 => 278,
+            4 => 558,
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn boltzmann() {
+        let scaling = Scaling::Boltzmann {
+            temperature: 10.0,
+            cooling: 0.5,
+            min_temperature: 1.0,
+        };
+
+        approx::assert_relative_eq!(scaling.temperature(0), 10.0);
+        approx::assert_relative_eq!(scaling.temperature(2), 2.5);
+        approx::assert_relative_eq!(scaling.temperature(100), 1.0);
+
+        // The hotter it is, the more even the chances are
+        let mut hot = vec![1.0, 2.0];
+        let mut cold = hot.clone();
+
+        scaling.apply(&mut hot, 0);
+        scaling.apply(&mut cold, 100);
+
+        approx::assert_relative_eq!(hot[0] / hot[1], (-0.1f32).exp());
+        approx::assert_relative_eq!(cold[0] / cold[1], (-1.0f32).exp());
+
+        let actual = histogram(Scaling::Boltzmann {
+            temperature: 1.0,
+            cooling: 1.0,
+            min_temperature: 1.0,
+        });
+
+        let expected = maplit::btreemap! {
+            1 => 32,
+            2 => 92,
+            3 => 222,
+            4 => 654,
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rank() {
+        let actual = scaled(Scaling::Rank, &[20.0, -5.0, 1000.0, 20.0]);
+        assert_eq!(actual, [2.5, 1.0, 4.0, 2.5]);
+
+        // Ranks of 1.0 - 4.0 are the same as raw fitness in this case
+        let actual = histogram(Scaling::Rank);
+
+        let expected = maplit::btreemap! {
+            1 => 98,
+            2 => 202,
+            3 => 278,
+            4 => 422,
+        };
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn is_valid() {
+        assert!(Scaling::Linear { multiplier: 2.0 }.is_valid());
+        assert!(!Scaling::Linear { multiplier: 0.5 }.is_valid());
+        assert!(!Scaling::Power { exponent: 0.0 }.is_valid());
+
+        assert!(!Scaling::Boltzmann {
+            temperature: 1.0,
+            cooling: 1.5,
+            min_temperature: 0.1,
+        }
+        .is_valid());
+    }
+}
@@ -2,8 +2,8 @@ mod roulette_wheel;
 pub use roulette_wheel::*;
 use crate::*;
 
-pub trait SelectionMethod {
-    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+pub trait SelectionMethod: MaybeSync {
+    fn select<'a, I, G>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
     where
-        I: Individual;
+        I: Individual<G>;
 }
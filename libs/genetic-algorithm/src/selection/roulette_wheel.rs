@@ -4,9 +4,9 @@ use crate::*;
 pub struct RouletteWheelSelection;
 
 impl SelectionMethod for RouletteWheelSelection {
-    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    fn select<'a, I, G>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
     where
-        I: Individual,
+        I: Individual<G>,
     {
         population
             .choose_weighted(rng, |individual| individual.fitness())
@@ -0,0 +1,278 @@
+use crate::*;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Speciation {
+    /// Maximum genotypic distance between an individual and a species'
+    /// representative for the individual to still belong to that species:
+    /// - small values = many small species
+    /// - large values = few big species
+    threshold: f32,
+}
+
+/// Group of genotypically similar individuals; `members` are indices into
+/// the population that has been speciated, the first one being the
+/// species' representative.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    members: Vec<usize>,
+}
+
+impl Speciation {
+    pub fn new(threshold: f32) -> Self {
+        assert!(threshold > 0.0);
+
+        Self { threshold }
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    pub fn speciate<I, G>(&self, population: &[I]) -> Vec<Species>
+    where
+        I: Individual<G>,
+        G: Gene,
+    {
+        let mut species: Vec<Species> = Vec::new();
+
+        for (idx, individual) in population.iter().enumerate() {
+            let chromosome = individual.chromosome();
+
+            let existing = species.iter_mut().find(|species| {
+                let representative = population[species.members[0]].chromosome();
+
+                chromosome.distance(representative) <= self.threshold
+            });
+
+            match existing {
+                Some(species) => species.members.push(idx),
+                None => species.push(Species { members: vec![idx] }),
+            }
+        }
+
+        species
+    }
+
+    /// Splits `count` children among `species` proportionally to each
+    /// species' shared fitness (that is: its average fitness), so that a
+    /// big species can't take over the population just by being big.
+    pub(crate) fn allot<I, G>(
+        &self,
+        population: &[I],
+        species: &[Species],
+        count: usize,
+    ) -> Vec<usize>
+    where
+        I: Individual<G>,
+    {
+        let shares: Vec<f32> = species
+            .iter()
+            .map(|species| species.shared_fitness(population).sum())
+            .collect();
+
+        let total: f32 = shares.iter().sum();
+
+        // Nobody scored anything - fall back to keeping species' sizes
+        let shares: Vec<f32> = if total > 0.0 {
+            shares.iter().map(|share| share / total).collect()
+        } else {
+            species
+                .iter()
+                .map(|species| species.len() as f32 / population.len() as f32)
+                .collect()
+        };
+
+        let exact: Vec<f32> = shares.iter().map(|share| share * count as f32).collect();
+        let mut allotment: Vec<usize> = exact.iter().map(|n| n.floor() as usize).collect();
+
+        // Hand out what's left after rounding down to the species with the
+        // largest remainders
+        let mut remainders: Vec<_> = exact
+            .iter()
+            .map(|n| n - n.floor())
+            .enumerate()
+            .collect();
+
+        remainders.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        let missing = count - allotment.iter().sum::<usize>();
+
+        for &(idx, _) in remainders.iter().cycle().take(missing) {
+            allotment[idx] += 1;
+        }
+
+        allotment
+    }
+}
+
+impl Species {
+    pub fn members(&self) -> &[usize] {
+        &self.members
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Explicit fitness sharing: each member's fitness is divided by the
+    /// size of its species.
+    pub fn shared_fitness<'a, I, G>(&'a self, population: &'a [I]) -> impl Iterator<Item = f32> + 'a
+    where
+        I: Individual<G>,
+    {
+        let size = self.len() as f32;
+
+        self.members
+            .iter()
+            .map(move |&idx| population[idx].fitness() / size)
+    }
+
+    pub(crate) fn shared<'a, I, G>(&self, population: &'a [I]) -> Vec<Shared<'a, I>>
+    where
+        I: Individual<G>,
+    {
+        self.members
+            .iter()
+            .zip(self.shared_fitness(population))
+            .map(|(&idx, fitness)| Shared::new(&population[idx], fitness))
+            .collect()
+    }
+}
+
+/// Borrowed individual with its fitness replaced, so that selection
+/// methods can work on shared (or penalized) fitness without cloning the
+/// population.
+pub(crate) struct Shared<'a, I> {
+    pub(crate) individual: &'a I,
+    fitness: f32,
+}
+
+impl<'a, I> Shared<'a, I> {
+    pub(crate) fn new(individual: &'a I, fitness: f32) -> Self {
+        Self {
+            individual,
+            fitness,
+        }
+    }
+}
+
+impl<I, G> Individual<G> for Shared<'_, I>
+where
+    I: Individual<G>,
+{
+    fn create(_: Chromosome<G>) -> Self {
+        panic!("not supported for Shared")
+    }
+
+    fn chromosome(&self) -> &Chromosome<G> {
+        self.individual.chromosome()
+    }
+
+    fn fitness(&self) -> f32 {
+        self.fitness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn individual(genes: &[f32]) -> TestIndividual {
+        TestIndividual::create(genes.iter().cloned().collect())
+    }
+
+    #[test]
+    fn distance() {
+        let a: Chromosome = vec![0.0, 0.0, 0.0].into_iter().collect();
+        let b: Chromosome = vec![1.0, 2.0, 2.0].into_iter().collect();
+
+        approx::assert_relative_eq!(a.distance(&b), 3.0);
+        approx::assert_relative_eq!(b.distance(&a), 3.0);
+        approx::assert_relative_eq!(a.distance(&a), 0.0);
+    }
+
+    #[test]
+    fn speciate() {
+        let population = vec![
+            individual(&[0.0, 0.0]),
+            individual(&[5.0, 5.0]),
+            individual(&[0.5, 0.0]),
+            individual(&[5.0, 4.5]),
+            individual(&[0.0, 0.5]),
+            individual(&[-5.0, 5.0]),
+        ];
+
+        let actual = Speciation::new(1.0).speciate(&population);
+
+        let expected = vec![
+            Species {
+                members: vec![0, 2, 4],
+            },
+            Species {
+                members: vec![1, 3],
+            },
+            Species { members: vec![5] },
+        ];
+
+        assert_eq!(actual, expected);
+    }
+
+    mod allot {
+        use super::*;
+
+        #[test]
+        fn shares_children_by_average_fitness() {
+            // Species #0 has more members, but both species are equally
+            // good on average, so both should get the same number of kids
+            let population = vec![
+                individual(&[1.0, 1.0]),
+                individual(&[1.0, 1.0]),
+                individual(&[1.0, 1.0]),
+                individual(&[9.0, -7.0]),
+            ];
+
+            let speciation = Speciation::new(1.0);
+            let species = speciation.speciate(&population);
+            let actual = speciation.allot(&population, &species, 4);
+
+            assert_eq!(actual, vec![2, 2]);
+        }
+
+        #[test]
+        fn hands_out_remainders() {
+            let population = vec![
+                individual(&[1.0]),
+                individual(&[5.0]),
+                individual(&[10.0]),
+            ];
+
+            let speciation = Speciation::new(1.0);
+            let species = speciation.speciate(&population);
+            let actual = speciation.allot(&population, &species, 5);
+
+            assert_eq!(actual.iter().sum::<usize>(), 5);
+            assert_eq!(actual, vec![0, 2, 3]);
+        }
+
+        #[test]
+        fn falls_back_to_sizes_given_zero_fitness() {
+            let population = vec![
+                individual(&[0.0, 0.0]),
+                individual(&[0.0, 0.0]),
+                individual(&[0.0, 0.0]),
+                individual(&[5.0, -5.0]),
+            ];
+
+            let speciation = Speciation::new(1.0);
+            let species = speciation.speciate(&population);
+            let actual = speciation.allot(&population, &species, 8);
+
+            assert_eq!(actual, vec![6, 2]);
+        }
+    }
+}
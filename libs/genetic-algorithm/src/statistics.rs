@@ -0,0 +1,78 @@
+use crate::*;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    min_fitness: f32,
+    max_fitness: f32,
+    avg_fitness: f32,
+    species: usize,
+}
+
+impl Statistics {
+    pub fn new<I, G>(population: &[I], species: usize) -> Self
+    where
+        I: Individual<G>,
+    {
+        assert!(!population.is_empty());
+
+        let mut min_fitness = population[0].fitness();
+        let mut max_fitness = min_fitness;
+        let mut sum_fitness = 0.0;
+
+        for individual in population {
+            let fitness = individual.fitness();
+
+            min_fitness = min_fitness.min(fitness);
+            max_fitness = max_fitness.max(fitness);
+            sum_fitness += fitness;
+        }
+
+        Self {
+            min_fitness,
+            max_fitness,
+            avg_fitness: sum_fitness / (population.len() as f32),
+            species,
+        }
+    }
+
+    pub fn min_fitness(&self) -> f32 {
+        self.min_fitness
+    }
+
+    pub fn max_fitness(&self) -> f32 {
+        self.max_fitness
+    }
+
+    pub fn avg_fitness(&self) -> f32 {
+        self.avg_fitness
+    }
+
+    /// Number of species the evolved population was divided into; always
+    /// 1 when speciation is disabled.
+    pub fn species(&self) -> usize {
+        self.species
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let population = vec![
+            TestIndividual::new(30.0),
+            TestIndividual::new(10.0),
+            TestIndividual::new(20.0),
+            TestIndividual::new(40.0),
+        ];
+
+        let stats = Statistics::new(&population, 2);
+
+        approx::assert_relative_eq!(stats.min_fitness(), 10.0);
+        approx::assert_relative_eq!(stats.max_fitness(), 40.0);
+        approx::assert_relative_eq!(stats.avg_fitness(), 25.0);
+        assert_eq!(stats.species(), 2);
+    }
+}
@@ -0,0 +1,451 @@
+use crate::*;
+
+/// Who makes room for a freshly bred child.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Replacement {
+    /// The least fit individual
+    Worst,
+
+    /// Individual that has survived the most steady-state steps
+    Oldest,
+
+    /// Anybody
+    Random,
+
+    /// The least fit out of `size` randomly chosen individuals - less
+    /// greedy than `Worst`, so it keeps more diversity around
+    TournamentLoser { size: usize },
+}
+
+/// Steady-state evolution: instead of replacing the whole population at
+/// once, each step breeds just a few children and decides who they replace,
+/// so that the caller can keep evolving individuals one at a time.
+///
+/// Keeps track of how many steps each individual has survived, which is
+/// what `Replacement::Oldest` goes by - for that to work, population passed
+/// to each step must be the previous one with the replacements applied.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SteadyState {
+    replacement: Replacement,
+
+    /// How many children get bred per step
+    children: usize,
+
+    ages: Vec<u32>,
+}
+
+impl SteadyState {
+    pub fn new(replacement: Replacement, children: usize) -> Self {
+        assert!(children > 0);
+
+        if let Replacement::TournamentLoser { size } = replacement {
+            assert!(size > 0);
+        }
+
+        Self {
+            replacement,
+            children,
+            ages: Vec::new(),
+        }
+    }
+
+    pub fn replacement(&self) -> Replacement {
+        self.replacement
+    }
+
+    pub fn children(&self) -> usize {
+        self.children
+    }
+
+    /// Number of steps each individual has survived so far
+    pub fn ages(&self) -> &[u32] {
+        &self.ages
+    }
+
+    /// Picks `count` distinct individuals that should be replaced and
+    /// updates ages accordingly.
+    pub(crate) fn replace<I, G>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        count: usize,
+    ) -> Vec<usize>
+    where
+        I: Individual<G>,
+    {
+        assert!(count <= population.len());
+
+        self.ages.resize(population.len(), 0);
+
+        let mut candidates: Vec<_> = (0..population.len()).collect();
+        let mut replaced = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let candidate = match self.replacement {
+                Replacement::Worst => Self::least_fit(population, &candidates),
+
+                Replacement::Oldest => (0..candidates.len())
+                    .max_by_key(|&candidate| {
+                        // (`Reverse` so that ties go to the first one)
+                        (
+                            self.ages[candidates[candidate]],
+                            std::cmp::Reverse(candidate),
+                        )
+                    })
+                    .unwrap(),
+
+                Replacement::Random => rng.gen_range(0..candidates.len()),
+
+                Replacement::TournamentLoser { size } => {
+                    let size = size.min(candidates.len());
+
+                    let contestants: Vec<_> =
+                        rand::seq::index::sample(rng, candidates.len(), size).into_vec();
+
+                    let loser = Self::least_fit(
+                        population,
+                        &contestants
+                            .iter()
+                            .map(|&contestant| candidates[contestant])
+                            .collect::<Vec<_>>(),
+                    );
+
+                    contestants[loser]
+                }
+            };
+
+            replaced.push(candidates.remove(candidate));
+        }
+
+        for age in &mut self.ages {
+            *age += 1;
+        }
+
+        for &idx in &replaced {
+            self.ages[idx] = 0;
+        }
+
+        replaced
+    }
+
+    /// Returns position (within `candidates`) of the least fit candidate.
+    fn least_fit<I, G>(population: &[I], candidates: &[usize]) -> usize
+    where
+        I: Individual<G>,
+    {
+        (0..candidates.len())
+            .min_by(|&a, &b| {
+                let a = population[candidates[a]].fitness();
+                let b = population[candidates[b]].fitness();
+
+                a.total_cmp(&b)
+            })
+            .expect("got an empty population")
+    }
+}
+
+impl<S, G> GeneticAlgorithm<S, G>
+where
+    S: SelectionMethod,
+    G: Gene,
+{
+    /// Steady-state counterpart of `evolve()`: breeds a few children (out
+    /// of the whole population) and returns them together with indices of
+    /// the individuals they should replace.
+    ///
+    /// Immigrants, restarts and speciation all work generation by
+    /// generation, so they can't be used here.
+    pub fn evolve_steady<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        steady_state: &mut SteadyState,
+    ) -> Vec<(usize, I)>
+    where
+        I: Individual<G>,
+    {
+        assert!(!population.is_empty());
+
+        self.assert_steady();
+
+        if self.adjusts_fitness() {
+            self.breed_steady(rng, &self.adjust(population), steady_state)
+        } else {
+            self.breed_steady(rng, population, steady_state)
+        }
+    }
+
+    fn breed_steady<P, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        parents: &[P],
+        steady_state: &mut SteadyState,
+    ) -> Vec<(usize, I)>
+    where
+        P: Individual<G>,
+        I: Individual<G>,
+    {
+        let count = steady_state.children.min(parents.len());
+        let origins: Vec<_> = (0..parents.len()).collect();
+
+        // (children are reported to observers under the indices of the
+        // individuals they replace)
+        steady_state
+            .replace(rng, parents, count)
+            .into_iter()
+            .map(|idx| (idx, self.breed(rng, parents, &origins, idx)))
+            .collect()
+    }
+
+    /// Breeds a single child of `parent` and a mate selected out of `mates`
+    /// (e.g. individuals that happen to be nearby) - selection, scaling,
+    /// crossover, mutation and repair work the same way they do in
+    /// `evolve()`, it's just that one of the parents is picked by the
+    /// caller; returns the child together with its mate.
+    ///
+    /// `parent` and `mates` are indices into `population`; a parent without
+    /// any mates gets crossed over with itself, and so do mates that have
+    /// got nothing to select by (i.e. none of them is fit at all). `idx` is
+    /// the index the child is going to have (for observers).
+    ///
+    /// Just like `evolve_steady()`, doesn't support immigrants, restarts
+    /// and speciation.
+    pub fn breed_with<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        parent: usize,
+        mates: &[usize],
+        idx: usize,
+    ) -> (I, usize)
+    where
+        I: Individual<G>,
+    {
+        self.assert_steady();
+
+        if self.adjusts_fitness() {
+            self.mate(rng, &self.adjust(population), parent, mates, idx)
+        } else {
+            self.mate(rng, population, parent, mates, idx)
+        }
+    }
+
+    fn assert_steady(&self) {
+        assert!(
+            self.immigrants == 0.0 && self.restart.is_none() && self.speciation.is_none(),
+            "immigrants, restarts and speciation aren't supported in the steady-state mode"
+        );
+    }
+
+    fn mate<P, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &[P],
+        parent: usize,
+        mates: &[usize],
+        idx: usize,
+    ) -> (I, usize)
+    where
+        P: Individual<G>,
+        I: Individual<G>,
+    {
+        // (roulette can't choose out of individuals that are all unfit, so
+        // we don't even try)
+        let candidates: Vec<_> = mates
+            .iter()
+            .map(|&mate| Shared::new(&population[mate], population[mate].fitness()))
+            .filter(|mate| mate.fitness() > 0.0)
+            .collect();
+
+        let mate = if candidates.is_empty() {
+            parent
+        } else {
+            let mate = self.selection_method.select(rng, &candidates);
+
+            let mate = mates
+                .iter()
+                .copied()
+                .find(|&candidate| std::ptr::eq(&population[candidate], mate.individual))
+                .unwrap();
+
+            for observer in &self.observers {
+                observer.selected(mate, population[mate].fitness());
+            }
+
+            mate
+        };
+
+        let child = self.offspring(
+            rng,
+            (&population[parent], parent),
+            (&population[mate], mate),
+            idx,
+        );
+
+        (child, mate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn population() -> Vec<TestIndividual> {
+        vec![
+            TestIndividual::new(3.0),
+            TestIndividual::new(1.0),
+            TestIndividual::new(4.0),
+            TestIndividual::new(2.0),
+            TestIndividual::new(5.0),
+        ]
+    }
+
+    fn replace(replacement: Replacement, count: usize) -> Vec<usize> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        SteadyState::new(replacement, count).replace(&mut rng, &population(), count)
+    }
+
+    #[test]
+    fn worst() {
+        assert_eq!(replace(Replacement::Worst, 2), [1, 3]);
+    }
+
+    #[test]
+    fn oldest() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut steady_state = SteadyState::new(Replacement::Oldest, 2);
+
+        // Everybody is equally old at first, so the first ones go
+        assert_eq!(steady_state.replace(&mut rng, &population(), 2), [0, 1]);
+        assert_eq!(steady_state.ages(), [0, 0, 1, 1, 1]);
+
+        assert_eq!(steady_state.replace(&mut rng, &population(), 2), [2, 3]);
+        assert_eq!(steady_state.replace(&mut rng, &population(), 2), [4, 0]);
+        assert_eq!(steady_state.ages(), [0, 2, 1, 1, 0]);
+    }
+
+    #[test]
+    fn random() {
+        let replaced = replace(Replacement::Random, 5);
+
+        let mut sorted = replaced.clone();
+        sorted.sort();
+
+        assert_eq!(sorted, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn tournament_loser() {
+        // Tournament of the whole population is the same as `Worst`
+        assert_eq!(replace(Replacement::TournamentLoser { size: 5 }, 2), [1, 3]);
+
+        // ... while with the smallest one anybody but the best can lose
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut steady_state = SteadyState::new(Replacement::TournamentLoser { size: 2 }, 1);
+
+        let losers: std::collections::BTreeSet<_> = (0..100)
+            .flat_map(|_| steady_state.replace(&mut rng, &population(), 1))
+            .collect();
+
+        assert_eq!(losers.len(), 4);
+        assert!(!losers.contains(&4));
+    }
+
+    #[test]
+    fn evolves_in_place() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        );
+
+        let mut steady_state = SteadyState::new(Replacement::Worst, 1);
+
+        let mut population: Vec<_> = (0..8)
+            .map(|n| TestIndividual::create([n as f32, 1.0, 2.0].into_iter().collect()))
+            .collect();
+
+        let avg_fitness = |population: &[TestIndividual]| {
+            population.iter().map(|i| i.fitness()).sum::<f32>() / population.len() as f32
+        };
+
+        let before = avg_fitness(&population);
+
+        for _ in 0..50 {
+            let replacements = ga.evolve_steady(&mut rng, &population, &mut steady_state);
+
+            assert_eq!(replacements.len(), 1);
+
+            for (idx, child) in replacements {
+                population[idx] = child;
+            }
+        }
+
+        assert_eq!(population.len(), 8);
+        assert!(avg_fitness(&population) > before);
+    }
+
+    #[test]
+    fn breeds_with_mate() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        );
+
+        let population: Vec<_> = [1.0, 0.0, 2.0, 3.0]
+            .into_iter()
+            .map(|gene| TestIndividual::create([gene, gene].into_iter().collect()))
+            .collect();
+
+        // Unfit mates never get chosen...
+        for _ in 0..10 {
+            let (child, mate): (TestIndividual, _) =
+                ga.breed_with(&mut rng, &population, 0, &[1, 2], 4);
+
+            assert_eq!(mate, 2);
+            assert!(child
+                .chromosome()
+                .iter()
+                .all(|&gene| gene == 1.0 || gene == 2.0));
+        }
+
+        // ... and without anybody fit around, the parent mates with itself
+        let (child, mate): (TestIndividual, _) = ga.breed_with(&mut rng, &population, 3, &[1], 4);
+
+        assert_eq!(mate, 3);
+        assert_eq!(child.chromosome(), population[3].chromosome());
+
+        let (_, mate): (TestIndividual, _) = ga.breed_with(&mut rng, &population, 3, &[], 4);
+
+        assert_eq!(mate, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "aren't supported in the steady-state mode")]
+    fn rejects_generational_settings() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        )
+        .with_immigrants(0.5);
+
+        ga.evolve_steady(
+            &mut rng,
+            &[TestIndividual::new(1.0)],
+            &mut SteadyState::new(Replacement::Worst, 1),
+        );
+    }
+}
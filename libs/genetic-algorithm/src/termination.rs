@@ -0,0 +1,486 @@
+use crate::*;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Tells when evolution is done.
+///
+/// Conditions can be combined with `Any` and `All`, e.g. "stop after 500
+/// generations or once fitness hasn't improved for 50 of them".
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "kebab-case"))]
+pub enum StopCondition {
+    /// Given number of generations has been evolved (since the controller
+    /// got created)
+    MaxGenerations { generations: usize },
+
+    /// Somebody has reached given fitness
+    TargetFitness { fitness: f32 },
+
+    /// Best fitness hasn't improved for given number of generations
+    Stagnation { generations: usize },
+
+    /// Given number of seconds has passed since the `RunController` got
+    /// created
+    ///
+    /// Relies on `std::time::Instant`, which isn't available on
+    /// `wasm32-unknown-unknown`.
+    TimeLimit { seconds: f32 },
+
+    /// Diversity (average distance between two chromosomes, see
+    /// `pairwise_distance()`) has fallen below given threshold, i.e. the
+    /// population has converged
+    DiversityCollapse { min_diversity: f32 },
+
+    /// Any of the conditions is met
+    Any { conditions: Vec<StopCondition> },
+
+    /// All of the conditions are met at once
+    All { conditions: Vec<StopCondition> },
+}
+
+impl StopCondition {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::MaxGenerations { generations } | Self::Stagnation { generations } => {
+                *generations > 0
+            }
+            Self::TargetFitness { fitness } => fitness.is_finite(),
+            Self::TimeLimit { seconds } => *seconds > 0.0,
+            Self::DiversityCollapse { min_diversity } => *min_diversity >= 0.0,
+
+            Self::Any { conditions } | Self::All { conditions } => {
+                !conditions.is_empty() && conditions.iter().all(Self::is_valid)
+            }
+        }
+    }
+
+    fn needs(&self, f: &impl Fn(&Self) -> bool) -> bool {
+        match self {
+            Self::Any { conditions } | Self::All { conditions } => {
+                conditions.iter().any(|condition| condition.needs(f))
+            }
+            condition => f(condition),
+        }
+    }
+
+    fn check(&self, progress: &Progress) -> Option<StopReason> {
+        match *self {
+            Self::MaxGenerations { generations } => (progress.generations >= generations)
+                .then_some(StopReason::MaxGenerations { generations }),
+
+            Self::TargetFitness { fitness } => {
+                (progress.fitness >= fitness).then_some(StopReason::TargetFitness {
+                    fitness: progress.fitness,
+                })
+            }
+
+            Self::Stagnation { generations } => {
+                (progress.stagnant >= generations).then_some(StopReason::Stagnation {
+                    generations: progress.stagnant,
+                    fitness: progress.best_fitness,
+                })
+            }
+
+            Self::TimeLimit { seconds } => {
+                (progress.elapsed.as_secs_f32() >= seconds).then_some(StopReason::TimeLimit {
+                    elapsed: progress.elapsed,
+                })
+            }
+
+            Self::DiversityCollapse { min_diversity } => (progress.diversity < min_diversity)
+                .then_some(StopReason::DiversityCollapse {
+                    diversity: progress.diversity,
+                }),
+
+            Self::Any { ref conditions } => conditions
+                .iter()
+                .find_map(|condition| condition.check(progress)),
+
+            Self::All { ref conditions } => conditions
+                .iter()
+                .map(|condition| condition.check(progress))
+                .collect::<Option<_>>()
+                .map(StopReason::All),
+        }
+    }
+}
+
+/// Why evolution has stopped.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StopReason {
+    MaxGenerations {
+        generations: usize,
+    },
+
+    TargetFitness {
+        fitness: f32,
+    },
+
+    Stagnation {
+        generations: usize,
+
+        /// Best fitness that has been reached
+        fitness: f32,
+    },
+
+    TimeLimit {
+        elapsed: Duration,
+    },
+
+    DiversityCollapse {
+        diversity: f32,
+    },
+
+    /// All of the conditions have been met; contains reasons for each of
+    /// them
+    All(Vec<StopReason>),
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxGenerations { generations } => {
+                write!(f, "evolved {} generations", generations)
+            }
+
+            Self::TargetFitness { fitness } => {
+                write!(f, "reached target fitness ({:.2})", fitness)
+            }
+
+            Self::Stagnation {
+                generations,
+                fitness,
+            } => write!(
+                f,
+                "best fitness ({:.2}) hasn't improved for {} generations",
+                fitness, generations
+            ),
+
+            Self::TimeLimit { elapsed } => {
+                write!(f, "ran out of time ({:.1}s)", elapsed.as_secs_f32())
+            }
+
+            Self::DiversityCollapse { diversity } => {
+                write!(f, "population has converged (diversity {:.4})", diversity)
+            }
+
+            Self::All(reasons) => {
+                for (idx, reason) in reasons.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, " and ")?;
+                    }
+
+                    write!(f, "{}", reason)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Keeps track of how evolution goes and decides when it's time to stop.
+///
+/// Works with anything that produces `Statistics` - either through `run()`,
+/// which drives an `Optimizer` on its own, or through `update()`, for
+/// callers evolving populations their own way (e.g. the simulation).
+#[derive(Clone, Debug)]
+pub struct RunController {
+    condition: StopCondition,
+    progress: Progress,
+    started: Option<Instant>,
+}
+
+#[derive(Clone, Debug)]
+struct Progress {
+    generations: usize,
+
+    /// Best fitness of the latest generation
+    fitness: f32,
+
+    /// Best fitness of all the generations so far
+    best_fitness: f32,
+
+    /// Number of generations since `best_fitness` has last improved
+    stagnant: usize,
+
+    elapsed: Duration,
+    diversity: f32,
+}
+
+/// Outcome of `RunController::run()`.
+#[derive(Clone, Debug)]
+pub struct Run<I> {
+    /// Population evolved out of the last generation
+    pub population: Vec<I>,
+
+    pub statistics: Vec<Statistics>,
+    pub reason: StopReason,
+}
+
+impl RunController {
+    pub fn new(condition: StopCondition) -> Self {
+        assert!(condition.is_valid());
+
+        // (the clock is started only when needed, so that controllers
+        // without a time limit work where there's no clock, too)
+        let started = condition
+            .needs(&|condition| matches!(condition, StopCondition::TimeLimit { .. }))
+            .then(Instant::now);
+
+        Self {
+            condition,
+            progress: Progress {
+                generations: 0,
+                fitness: f32::NEG_INFINITY,
+                best_fitness: f32::NEG_INFINITY,
+                stagnant: 0,
+                elapsed: Duration::ZERO,
+                diversity: f32::INFINITY,
+            },
+            started,
+        }
+    }
+
+    pub fn condition(&self) -> &StopCondition {
+        &self.condition
+    }
+
+    /// Number of generations seen so far
+    pub fn generations(&self) -> usize {
+        self.progress.generations
+    }
+
+    /// Notes down a generation that's just been evaluated - `statistics`
+    /// describe it and `population` is what's going to be evolved next
+    /// (diversity is measured on it, and only if there's a condition that
+    /// needs it).
+    ///
+    /// Returns why evolution should stop, if it should.
+    pub fn update<I, G>(&mut self, statistics: &Statistics, population: &[I]) -> Option<StopReason>
+    where
+        I: Individual<G>,
+        G: Gene,
+    {
+        let progress = &mut self.progress;
+        let fitness = statistics.max_fitness();
+
+        progress.generations += 1;
+        progress.fitness = fitness;
+
+        if fitness > progress.best_fitness {
+            progress.best_fitness = fitness;
+            progress.stagnant = 0;
+        } else {
+            progress.stagnant += 1;
+        }
+
+        if let Some(started) = self.started {
+            progress.elapsed = started.elapsed();
+        }
+
+        if self
+            .condition
+            .needs(&|condition| matches!(condition, StopCondition::DiversityCollapse { .. }))
+        {
+            progress.diversity = pairwise_distance(population);
+        }
+
+        self.condition.check(progress)
+    }
+
+    /// Keeps evolving given population until one of the conditions is met.
+    pub fn run<I, O>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        optimizer: &mut O,
+        mut population: Vec<I>,
+    ) -> Run<I>
+    where
+        I: Individual,
+        O: Optimizer<I> + ?Sized,
+    {
+        let mut statistics = Vec::new();
+
+        loop {
+            let (new_population, stats) = optimizer.evolve(rng, &population);
+            let reason = self.update(&stats, &new_population);
+
+            population = new_population;
+            statistics.push(stats);
+
+            if let Some(reason) = reason {
+                return Run {
+                    population,
+                    statistics,
+                    reason,
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn statistics(max_fitness: f32) -> Statistics {
+        Statistics::new(&[TestIndividual::new(max_fitness)], 1)
+    }
+
+    fn population(genes: &[f32]) -> Vec<TestIndividual> {
+        genes
+            .iter()
+            .map(|&gene| TestIndividual::create([gene].into_iter().collect()))
+            .collect()
+    }
+
+    #[test]
+    fn max_generations() {
+        let mut controller = RunController::new(StopCondition::MaxGenerations { generations: 3 });
+        let population = population(&[1.0]);
+
+        assert_eq!(controller.update(&statistics(1.0), &population), None);
+        assert_eq!(controller.update(&statistics(1.0), &population), None);
+
+        assert_eq!(
+            controller.update(&statistics(1.0), &population),
+            Some(StopReason::MaxGenerations { generations: 3 })
+        );
+    }
+
+    #[test]
+    fn stagnation() {
+        let mut controller = RunController::new(StopCondition::Stagnation { generations: 2 });
+        let population = population(&[1.0]);
+
+        let reasons: Vec<_> = [1.0, 2.0, 2.0, 3.0, 1.0, 3.0]
+            .into_iter()
+            .map(|fitness| controller.update(&statistics(fitness), &population))
+            .collect();
+
+        assert_eq!(
+            reasons,
+            [
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(StopReason::Stagnation {
+                    generations: 2,
+                    fitness: 3.0
+                })
+            ]
+        );
+    }
+
+    #[test]
+    fn time_limit() {
+        let mut controller = RunController::new(StopCondition::TimeLimit { seconds: 0.01 });
+
+        std::thread::sleep(Duration::from_millis(20));
+
+        // Time spent evolving the very first generation counts, too
+        assert!(matches!(
+            controller.update(&statistics(1.0), &population(&[1.0])),
+            Some(StopReason::TimeLimit { .. })
+        ));
+    }
+
+    #[test]
+    fn diversity_collapse() {
+        let mut controller =
+            RunController::new(StopCondition::DiversityCollapse { min_diversity: 0.5 });
+
+        // Pairwise distances: 1.0, 3.0 and 2.0
+        assert_eq!(pairwise_distance(&population(&[0.0, 1.0, 3.0])), 2.0);
+
+        assert_eq!(
+            controller.update(&statistics(1.0), &population(&[0.0, 1.0, 3.0])),
+            None
+        );
+
+        assert_eq!(
+            controller.update(&statistics(1.0), &population(&[1.0, 1.0, 1.0, 1.5])),
+            Some(StopReason::DiversityCollapse { diversity: 0.25 })
+        );
+    }
+
+    #[test]
+    fn composition() {
+        let condition = StopCondition::Any {
+            conditions: vec![
+                StopCondition::MaxGenerations { generations: 10 },
+                StopCondition::All {
+                    conditions: vec![
+                        StopCondition::TargetFitness { fitness: 5.0 },
+                        StopCondition::Stagnation { generations: 1 },
+                    ],
+                },
+            ],
+        };
+
+        assert!(condition.is_valid());
+        assert!(!StopCondition::All { conditions: vec![] }.is_valid());
+
+        let mut controller = RunController::new(condition);
+        let population = population(&[1.0]);
+
+        // Target fitness gets reached, but it's still improving
+        assert_eq!(controller.update(&statistics(4.0), &population), None);
+        assert_eq!(controller.update(&statistics(6.0), &population), None);
+
+        let reason = controller.update(&statistics(5.5), &population).unwrap();
+
+        assert_eq!(
+            reason,
+            StopReason::All(vec![
+                StopReason::TargetFitness { fitness: 5.5 },
+                StopReason::Stagnation {
+                    generations: 1,
+                    fitness: 6.0
+                },
+            ])
+        );
+
+        assert_eq!(
+            reason.to_string(),
+            "reached target fitness (5.50) and best fitness (6.00) hasn't improved for 1 generations"
+        );
+    }
+
+    #[test]
+    fn run() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        );
+
+        let population: Vec<_> = (0..10)
+            .map(|n| TestIndividual::create([n as f32, 1.0, 2.0].into_iter().collect()))
+            .collect();
+
+        let mut controller = RunController::new(StopCondition::Any {
+            conditions: vec![
+                StopCondition::TargetFitness { fitness: 20.0 },
+                StopCondition::MaxGenerations { generations: 1000 },
+                StopCondition::TimeLimit { seconds: 60.0 },
+            ],
+        });
+
+        let run = controller.run(&mut rng, &mut ga, population);
+
+        assert!(matches!(run.reason, StopReason::TargetFitness { fitness } if fitness >= 20.0));
+        assert_eq!(run.statistics.len(), controller.generations());
+        assert!(run.statistics.len() < 1000);
+        assert_eq!(run.population.len(), 10);
+    }
+}
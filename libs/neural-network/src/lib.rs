@@ -8,12 +8,13 @@ use self::neuron::*;
 
 
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct LayerTopology {
     pub neurons: usize,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Network {
     layers: Vec<Layer>,
 }
@@ -48,6 +49,19 @@ impl Network {
             .cloned()
     }
 
+    /// Returns topology this network has been created with.
+    pub fn topology(&self) -> Vec<LayerTopology> {
+        let inputs = LayerTopology {
+            neurons: self.layers[0].neurons[0].weights.len(),
+        };
+
+        once(inputs)
+            .chain(self.layers.iter().map(|layer| LayerTopology {
+                neurons: layer.neurons.len(),
+            }))
+            .collect()
+    }
+
     pub fn random(rng: &mut dyn RngCore, layers: &[LayerTopology]) -> Self {
         assert!(layers.len() > 1);
 
@@ -64,6 +78,20 @@ impl Network {
             .iter()
             .fold(inputs, |inputs, layer| layer.propagate(inputs))
     }
+
+    /// Works like `propagate()`, but returns outputs of all the layers
+    /// instead of just the last one - handy for visualising what the
+    /// network is "thinking".
+    pub fn activations(&self, mut inputs: Vec<f32>) -> Vec<Vec<f32>> {
+        let mut activations = Vec::with_capacity(self.layers.len());
+
+        for layer in &self.layers {
+            inputs = layer.propagate(inputs);
+            activations.push(inputs.clone());
+        }
+
+        activations
+    }
 }
 
 #[cfg(test)]
@@ -105,6 +133,15 @@ mod tests {
 
         assert_eq!(network.layers[1].neurons.len(), 1);
 
+        assert_eq!(
+            network.topology(),
+            [
+                LayerTopology { neurons: 3 },
+                LayerTopology { neurons: 2 },
+                LayerTopology { neurons: 1 },
+            ]
+        );
+
         assert_relative_eq!(
             network.layers[1].neurons[0].weights.as_slice(),
             &[-0.48879623, -0.19277143].as_slice()
@@ -140,6 +177,26 @@ mod tests {
         assert_relative_eq!(actual.as_slice(), expected.as_slice());
     }
 
+    #[test]
+    fn activations() {
+        let layers = (
+            Layer::new(vec![
+                Neuron::new(0.0, vec![-0.5, -0.4, -0.3]),
+                Neuron::new(0.0, vec![-0.2, -0.1, 0.0]),
+            ]),
+            Layer::new(vec![Neuron::new(0.0, vec![-0.5, 0.5])]),
+        );
+        let network = Network::new(vec![layers.0.clone(), layers.1.clone()]);
+
+        let actual = network.activations(vec![0.5, 0.6, 0.7]);
+        let hidden = layers.0.propagate(vec![0.5, 0.6, 0.7]);
+        let output = layers.1.propagate(hidden.clone());
+
+        assert_eq!(actual.len(), 2);
+        assert_relative_eq!(actual[0].as_slice(), hidden.as_slice());
+        assert_relative_eq!(actual[1].as_slice(), output.as_slice());
+    }
+
     #[test]
     fn weights() {
         let network = Network::new(vec![
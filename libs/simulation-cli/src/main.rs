@@ -0,0 +1,102 @@
+//! Runs a scenario headlessly, printing statistics of each generation:
+//!
+//! ```text
+//! simulation-cli <scenario> [generations] [checkpoint] [lineage]
+//! ```
+//!
+//! Evolution goes on for given number of generations or until the
+//! scenario's `stop` condition is met, whichever comes first (when neither
+//! is given, it stops after 100 generations) - the reason gets printed at
+//! the end.
+//!
+//! When `checkpoint` is given, the simulation is saved there at the end,
+//! so that it can be inspected (or continued) later.
+//!
+//! When `lineage` is given, the family tree of all the birds is exported
+//! there - as a GraphViz graph if the file ends with `.dot`, as JSON
+//! otherwise (checkpoint can be left empty, i.e. `""`, to export just the
+//! lineage).
+
+use lib_genetic_algorithm as ga;
+use lib_simulation as sim;
+use std::{env, error::Error, fs, process};
+
+const USAGE: &str = "usage: simulation-cli <scenario> [generations] [checkpoint] [lineage]";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let scenario = args.next().ok_or(USAGE)?;
+
+    let generations = match args.next() {
+        Some(generations) => Some(generations.parse()?),
+        None => None,
+    };
+
+    let checkpoint = args.next().filter(|checkpoint| !checkpoint.is_empty());
+    let lineage = args.next();
+
+    let scenario = fs::read_to_string(&scenario)
+        .map_err(|err| format!("couldn't read `{}`: {}", scenario, err))?;
+
+    let scenario = sim::Scenario::parse(&scenario)?;
+    let params = scenario.params();
+    let (mut simulation, mut rng) = scenario.build();
+
+    let condition = match (generations, scenario.stop.clone()) {
+        (Some(generations), Some(stop)) => ga::StopCondition::Any {
+            conditions: vec![ga::StopCondition::MaxGenerations { generations }, stop],
+        },
+        (Some(generations), None) => ga::StopCondition::MaxGenerations { generations },
+        (None, Some(stop)) => stop,
+        (None, None) => ga::StopCondition::MaxGenerations { generations: 100 },
+    };
+
+    if !condition.is_valid() {
+        return Err("generations must be positive".into());
+    }
+
+    if lineage.is_some() {
+        simulation = simulation.with_lineage();
+    }
+
+    let mut controller = ga::RunController::new(condition);
+
+    let reason = simulation.run(&mut rng, &params, &mut controller, |stats| {
+        println!(
+            "generation {}: min={:.2}, avg={:.2}, max={:.2}, species={}, meals={:?}, diversity={:.2}, behavioural_diversity={:.2}",
+            stats.generation,
+            stats.ga.min_fitness(),
+            stats.ga.avg_fitness(),
+            stats.ga.max_fitness(),
+            stats.ga.species(),
+            stats.meals,
+            stats.diversity.pairwise_distance(),
+            stats.behavioural_diversity,
+        );
+    });
+
+    println!("stopped: {}", reason);
+
+    if let Some(checkpoint) = checkpoint {
+        simulation.save_checkpoint(&rng, fs::File::create(checkpoint)?)?;
+    }
+
+    if let (Some(path), Some(lineage)) = (lineage, simulation.lineage()) {
+        let lineage = if path.ends_with(".dot") {
+            lineage.to_dot()
+        } else {
+            lineage.to_json()
+        };
+
+        fs::write(path, lineage)?;
+    }
+
+    Ok(())
+}
@@ -1,11 +1,70 @@
+mod worker;
+
+pub use self::worker::*;
+
 use lib_simulation as sim;
 use rand::prelude::*;
+use serde::Serialize;
+use sim::ChaCha8Rng;
 use wasm_bindgen::prelude::*;
 
+/// Number of floats describing a single animal in `animals_view()`:
+/// x, y and rotation.
+const ANIMAL_STRIDE: usize = 3;
+
+/// Number of floats describing a single food in `foods_view()`: x, y and
+/// nutrition (negative for poison).
+const FOOD_STRIDE: usize = 3;
+
+/// Number of floats describing a single obstacle in `obstacles_view()`:
+/// x, y and radius.
+const OBSTACLE_STRIDE: usize = 3;
+
 #[wasm_bindgen]
 pub struct Simulation {
-    rng: ThreadRng,
+    rng: ChaCha8Rng,
     sim: sim::Simulation,
+    recorder: sim::Recorder,
+    animals_buf: Vec<f32>,
+    foods_buf: Vec<f32>,
+    obstacles_buf: Vec<f32>,
+}
+
+#[wasm_bindgen]
+pub struct Player {
+    player: sim::Player,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct StepParams {
+    pub speed_min: f32,
+    pub speed_max: f32,
+    pub speed_accel: f32,
+    pub rotation_accel: f32,
+    pub generation_length: i32,
+    pub fov_range: f32,
+    pub fov_angle: f32,
+    pub cells: usize,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, Serialize)]
+pub struct Statistics {
+    pub generation: i32,
+    pub min_fitness: f32,
+    pub max_fitness: f32,
+    pub avg_fitness: f32,
+    pub species: usize,
+
+    /// How many pieces of each kind of food have been eaten
+    #[wasm_bindgen(getter_with_clone)]
+    pub meals: Vec<usize>,
+
+    /// Average distance between two birds' chromosomes
+    pub diversity: f32,
+
+    pub behavioural_diversity: f32,
 }
 
 #[wasm_bindgen]
@@ -26,39 +85,238 @@ pub struct Animal {
     pub rotation: f32,
 }
 
+/// Everything there is to know about a single animal, including what it
+/// sees and thinks - see `Simulation::animal()`.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Serialize)]
+pub struct AnimalDetails {
+    pub x: f32,
+    pub y: f32,
+    pub rotation: f32,
+    pub satiation: f32,
+    pub fov_range: f32,
+    pub fov_angle: f32,
+
+    /// Number of food kinds the bird's eye tells apart
+    pub channels: usize,
+
+    /// What the bird currently sees, `channels` values per eye cell
+    #[wasm_bindgen(getter_with_clone)]
+    pub vision: Vec<f32>,
+
+    /// Activations of brain's hidden layer
+    #[wasm_bindgen(getter_with_clone)]
+    pub hidden: Vec<f32>,
+
+    /// Activations of brain's output layer: speed & rotation
+    #[wasm_bindgen(getter_with_clone)]
+    pub output: Vec<f32>,
+
+    #[wasm_bindgen(getter_with_clone)]
+    pub chromosome: Vec<f32>,
+}
+
 impl From<&sim::World> for World {
     fn from(world: &sim::World) -> Self {
-        let animals = world.animals().iter().map(Animal::from).collect();
-        let foods = world.foods().iter().map(Food::from).collect();
+        let animals = world
+            .animals()
+            .iter()
+            .map(|animal| Animal::new(animal, world))
+            .collect();
+
+        let foods = world
+            .foods()
+            .iter()
+            .map(|food| Food::new(food, world))
+            .collect();
 
         Self { animals, foods }
     }
 }
 
-impl From<&sim::Animal> for Animal {
-    fn from(animal: &sim::Animal) -> Self {
+impl Animal {
+    fn new(animal: &sim::Animal, world: &sim::World) -> Self {
+        let [x, y] = relative(world.arena(), animal.position().x, animal.position().y);
+
         Self {
-            x: animal.position().x,
-            y: animal.position().y,
+            x,
+            y,
             rotation: animal.rotation().angle(),
         }
     }
 }
 
+impl AnimalDetails {
+    fn new(animal: &sim::Animal, world: &sim::World) -> Self {
+        let vision = animal.vision(world.foods());
+        let mut activations = animal.activations(vision.clone());
+        let output = activations.pop().unwrap_or_default();
+        let hidden = activations.pop().unwrap_or_default();
+        let [x, y] = relative(world.arena(), animal.position().x, animal.position().y);
+
+        Self {
+            x,
+            y,
+            rotation: animal.rotation().angle(),
+            satiation: animal.satiation(),
+            fov_range: animal.eye().fov_range() / world.arena().width,
+            fov_angle: animal.eye().fov_angle(),
+            channels: animal.eye().channels(),
+            vision,
+            hidden,
+            output,
+            chromosome: animal.as_chromosome().into_iter().collect(),
+        }
+    }
+}
+
 #[wasm_bindgen]
 impl Simulation {
     #[wasm_bindgen(constructor)]
     pub fn new(animals: i32, foods: i32, fov_range: f32, fov_angle: f32, cells: usize) -> Self {
-        let mut rng = thread_rng();
+        let mut rng = ChaCha8Rng::from_rng(thread_rng()).unwrap();
         let sim = sim::Simulation::random(&mut rng, animals, foods, fov_range, fov_angle, cells);
 
-        Self { rng, sim }
+        Self::from_sim(rng, sim)
+    }
+
+    /// Creates a simulation with given genome (as returned from e.g.
+    /// `export_best()`) living in it among random birds.
+    pub fn from_genome(genome: &str, animals: i32, foods: i32) -> Result<Simulation, JsError> {
+        let genome = sim::Genome::load(genome.as_bytes())?;
+        let mut rng = ChaCha8Rng::from_rng(thread_rng()).unwrap();
+        let sim = sim::Simulation::from_genomes(&mut rng, vec![genome], animals, foods)?;
+
+        Ok(Self::from_sim(rng, sim))
+    }
+
+    /// Creates simulation described by given scenario (see
+    /// `lib_simulation::Scenario`), in TOML or JSON.
+    pub fn from_scenario(scenario: &str) -> Result<Simulation, JsError> {
+        let (sim, rng) = sim::Scenario::parse(scenario)?.build();
+
+        Ok(Self::from_sim(rng, sim))
+    }
+
+    fn from_sim(rng: ChaCha8Rng, sim: sim::Simulation) -> Self {
+        // Recording is cheap (we store only the initial state and slider
+        // changes), so we keep it running all the time - this way whatever
+        // interesting happens can be exported after the fact
+        let recorder = sim::Recorder::new(&sim, &rng);
+
+        Self {
+            rng,
+            sim,
+            recorder,
+            animals_buf: Vec::new(),
+            foods_buf: Vec::new(),
+            obstacles_buf: Vec::new(),
+        }
     }
 
     pub fn world(&self) -> World {
         World::from(self.sim.world())
     }
 
+    /// Returns animals as a flat `[x, y, rotation, x, y, rotation, ...]`
+    /// array that points straight into WASM memory, so that rendering
+    /// doesn't have to allocate an object per animal each frame.
+    ///
+    /// Positions (here and in other views) are relative to the arena, i.e.
+    /// within `0.0..=1.0` regardless of its size.
+    ///
+    /// The view gets invalidated by the next call into the simulation (as
+    /// that can grow WASM memory) - it must be used right away and never
+    /// kept around.
+    pub fn animals_view(&mut self) -> js_sys::Float32Array {
+        let world = self.sim.world();
+
+        self.animals_buf.clear();
+
+        for animal in world.animals() {
+            let [x, y] = relative(world.arena(), animal.position().x, animal.position().y);
+
+            self.animals_buf.extend([x, y, animal.rotation().angle()]);
+        }
+
+        // SAFETY: the buffer is not touched again until the next call
+        // into the simulation, which the caller must not outlive (see
+        // above)
+        unsafe { js_sys::Float32Array::view(&self.animals_buf) }
+    }
+
+    /// Returns foods as a flat `[x, y, nutrition, x, y, nutrition, ...]`
+    /// array; see `animals_view()` for the caveats.
+    pub fn foods_view(&mut self) -> js_sys::Float32Array {
+        let world = self.sim.world();
+
+        self.foods_buf.clear();
+
+        for food in world.foods() {
+            self.foods_buf.extend(relative(
+                world.arena(),
+                food.position().x,
+                food.position().y,
+            ));
+
+            self.foods_buf.push(nutrition(food, world));
+        }
+
+        // SAFETY: see `animals_view()`
+        unsafe { js_sys::Float32Array::view(&self.foods_buf) }
+    }
+
+    /// Returns obstacles as a flat `[x, y, radius, ...]` array, with radius
+    /// relative to the arena's width; see `animals_view()` for the caveats.
+    pub fn obstacles_view(&mut self) -> js_sys::Float32Array {
+        let arena = self.sim.world().arena();
+
+        self.obstacles_buf.clear();
+
+        for obstacle in &arena.obstacles {
+            let [x, y] = relative(arena, obstacle.position.x, obstacle.position.y);
+
+            self.obstacles_buf
+                .extend([x, y, obstacle.radius / arena.width]);
+        }
+
+        // SAFETY: see `animals_view()`
+        unsafe { js_sys::Float32Array::view(&self.obstacles_buf) }
+    }
+
+    /// Returns details of a single animal (e.g. the one selected by user),
+    /// including what it sees and thinks; cheaper than going through
+    /// `world()` when only one animal is of interest.
+    pub fn animal(&self, idx: usize) -> Option<AnimalDetails> {
+        let world = self.sim.world();
+
+        world
+            .animals()
+            .get(idx)
+            .map(|animal| AnimalDetails::new(animal, world))
+    }
+
+    pub fn animal_count(&self) -> usize {
+        self.sim.world().animals().len()
+    }
+
+    pub fn food_count(&self) -> usize {
+        self.sim.world().foods().len()
+    }
+
+    pub fn animal_stride(&self) -> usize {
+        ANIMAL_STRIDE
+    }
+
+    pub fn food_stride(&self) -> usize {
+        FOOD_STRIDE
+    }
+
+    pub fn obstacle_stride(&self) -> usize {
+        OBSTACLE_STRIDE
+    }
+
+    #[allow(clippy::too_many_arguments)] // parameters come straight from the sliders
     pub fn step(
         &mut self,
         speed_min: f32,
@@ -70,8 +328,7 @@ impl Simulation {
         fov_angle: f32,
         cells: usize,
     ) {
-        self.sim.step(
-            &mut self.rng,
+        let params = StepParams::new(
             speed_min,
             speed_max,
             speed_accel,
@@ -81,11 +338,213 @@ impl Simulation {
             fov_angle,
             cells,
         );
+
+        self.step_once(&(&params).into());
+    }
+
+    /// Performs given number of steps without rendering anything in
+    /// between; returns statistics of generations finished along the way
+    pub fn step_many(&mut self, steps: u32, params: &StepParams) -> Vec<Statistics> {
+        let params = params.into();
+
+        (0..steps)
+            .filter_map(|_| self.step_once(&params))
+            .map(|stats| Statistics::from(&stats))
+            .collect()
+    }
+
+    /// Keeps stepping until given number of generations has finished
+    pub fn train_generations(&mut self, generations: u32, params: &StepParams) -> Vec<Statistics> {
+        let params = params.into();
+        let mut history = Vec::new();
+
+        while history.len() < generations as usize {
+            if let Some(stats) = self.step_once(&params) {
+                history.push(Statistics::from(&stats));
+            }
+        }
+
+        history
+    }
+
+    /// Keeps stepping for (roughly) given number of milliseconds, so that
+    /// fast-forwarding can be spread across animation frames without
+    /// freezing the page
+    pub fn train_for(&mut self, millis: f64, params: &StepParams) -> Vec<Statistics> {
+        self.run_for(millis, &params.into())
+            .iter()
+            .map(Statistics::from)
+            .collect()
+    }
+
+    fn run_for(&mut self, millis: f64, params: &sim::StepParams) -> Vec<sim::Statistics> {
+        // Asking for current time is not free, so we do that only every
+        // once in a while
+        const STEPS_PER_CHECK: usize = 16;
+
+        let deadline = js_sys::Date::now() + millis;
+        let mut history = Vec::new();
+
+        while js_sys::Date::now() < deadline {
+            for _ in 0..STEPS_PER_CHECK {
+                history.extend(self.step_once(params));
+            }
+        }
+
+        history
+    }
+
+    fn step_once(&mut self, params: &sim::StepParams) -> Option<sim::Statistics> {
+        self.recorder.step(&mut self.sim, &mut self.rng, params)
+    }
+
+    /// Returns genome of the animal that has eaten the most in the current
+    /// generation, in JSON
+    pub fn export_best(&self) -> Result<String, JsError> {
+        let animal = self
+            .sim
+            .best_animal()
+            .ok_or_else(|| JsError::new("there are no animals"))?;
+
+        export_genome(animal)
+    }
+
+    /// Returns genome of given animal, in JSON
+    pub fn export_animal(&self, idx: usize) -> Result<String, JsError> {
+        let animal = self
+            .sim
+            .world()
+            .animals()
+            .get(idx)
+            .ok_or_else(|| JsError::new("there's no such animal"))?;
+
+        export_genome(animal)
+    }
+
+    /// Puts given genome into the current population, in place of the bird
+    /// that has eaten the least so far.
+    ///
+    /// Since that's not something a replay could reproduce, recording
+    /// starts over from here.
+    pub fn inject(&mut self, genome: &str) -> Result<(), JsError> {
+        let genome = sim::Genome::load(genome.as_bytes())?;
+
+        self.sim.inject(&mut self.rng, vec![genome])?;
+        self.recorder = sim::Recorder::new(&self.sim, &self.rng);
+
+        Ok(())
     }
 
     pub fn get_generation(&mut self) -> i32 {
         self.sim.generation
     }
+
+    /// Returns everything that has happened since this simulation was
+    /// created as a replay, in JSON
+    pub fn export_replay(&self) -> Result<String, JsError> {
+        let mut replay = Vec::new();
+        self.recorder.replay().save(&mut replay)?;
+
+        Ok(String::from_utf8(replay)?)
+    }
+}
+
+fn export_genome(animal: &sim::Animal) -> Result<String, JsError> {
+    let mut genome = Vec::new();
+    animal.genome().save(&mut genome)?;
+
+    Ok(String::from_utf8(genome)?)
+}
+
+#[wasm_bindgen]
+impl StepParams {
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::too_many_arguments)] // parameters come straight from the sliders
+    pub fn new(
+        speed_min: f32,
+        speed_max: f32,
+        speed_accel: f32,
+        rotation_accel: f32,
+        generation_length: i32,
+        fov_range: f32,
+        fov_angle: f32,
+        cells: usize,
+    ) -> Self {
+        Self {
+            speed_min,
+            speed_max,
+            speed_accel,
+            rotation_accel,
+            generation_length,
+            fov_range,
+            fov_angle,
+            cells,
+        }
+    }
+}
+
+impl From<&StepParams> for sim::StepParams {
+    fn from(params: &StepParams) -> Self {
+        Self {
+            speed_min: params.speed_min,
+            speed_max: params.speed_max,
+            speed_accel: params.speed_accel,
+            rotation_accel: params.rotation_accel,
+            generation_length: params.generation_length,
+            fov_range: params.fov_range,
+            fov_angle: params.fov_angle,
+            cells: params.cells,
+        }
+    }
+}
+
+impl From<&sim::Statistics> for Statistics {
+    fn from(stats: &sim::Statistics) -> Self {
+        Self {
+            generation: stats.generation,
+            min_fitness: stats.ga.min_fitness(),
+            max_fitness: stats.ga.max_fitness(),
+            avg_fitness: stats.ga.avg_fitness(),
+            species: stats.ga.species(),
+            meals: stats.meals.clone(),
+            diversity: stats.diversity.pairwise_distance(),
+            behavioural_diversity: stats.behavioural_diversity,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Player {
+    #[wasm_bindgen(constructor)]
+    pub fn new(replay: &str) -> Result<Player, JsError> {
+        let replay = sim::Replay::load(replay.as_bytes())?;
+
+        Ok(Self {
+            player: sim::Player::new(replay),
+        })
+    }
+
+    pub fn world(&self) -> World {
+        World::from(self.player.simulation().world())
+    }
+
+    pub fn step(&mut self) {
+        self.player.step();
+    }
+
+    /// Jumps to the beginning of given generation; returns `false` if the
+    /// replay ends before that
+    pub fn seek(&mut self, generation: i32) -> bool {
+        self.player.seek(generation)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.player.is_finished()
+    }
+
+    pub fn get_generation(&self) -> i32 {
+        self.player.simulation().generation
+    }
 }
 
 #[wasm_bindgen]
@@ -93,13 +552,26 @@ impl Simulation {
 pub struct Food {
     pub x: f32,
     pub y: f32,
+    pub nutrition: f32,
 }
 
-impl From<&sim::Food> for Food {
-    fn from(food: &sim::Food) -> Self {
+impl Food {
+    fn new(food: &sim::Food, world: &sim::World) -> Self {
+        let [x, y] = relative(world.arena(), food.position().x, food.position().y);
+
         Self {
-            x: food.position().x,
-            y: food.position().y,
+            x,
+            y,
+            nutrition: nutrition(food, world),
         }
     }
 }
+
+fn nutrition(food: &sim::Food, world: &sim::World) -> f32 {
+    world.food().config().kinds[food.kind()].nutrition
+}
+
+/// Converts position within the arena into one relative to its size.
+fn relative(arena: &sim::Arena, x: f32, y: f32) -> [f32; 2] {
+    [x / arena.width, y / arena.height]
+}
@@ -0,0 +1,279 @@
+use crate::*;
+use js_sys::{Array, Float32Array, Object, Reflect};
+use serde::Deserialize;
+
+/// Runs the simulation off the main thread.
+///
+/// The worker script feeds each message it receives into `handle()` and
+/// posts back whatever events that returns, so that the page only has to
+/// render frames as they arrive:
+///
+/// ```js
+/// const worker = new sim.Worker();
+///
+/// onmessage = (message) => {
+///   for (const event of worker.handle(message.data)) {
+///     postMessage(event, sim.Worker.transferables(event));
+///   }
+/// };
+/// ```
+///
+/// # Commands
+///
+/// - `{ type: "configure", animals, foods, fov_range, fov_angle, cells,
+///   genome? }` starts a new simulation (with given genome living in it),
+/// - `{ type: "step", params, steps? }` performs given number of steps
+///   (one by default),
+/// - `{ type: "fast-forward", params, millis }` keeps stepping for given
+///   number of milliseconds,
+/// - `{ type: "snapshot" }` just reports the current state,
+/// - `{ type: "export-replay" }` reports everything that has happened so
+///   far as a replay,
+/// - `{ type: "export-genome", animal? }` reports genome of given animal
+///   (or of the best one),
+/// - `{ type: "inject", genome }` puts given genome into the population,
+/// - `{ type: "load-scenario", scenario }` starts a new simulation described
+///   by given scenario (see `lib_simulation::Scenario`).
+///
+/// `params` mirror `StepParams`; each command can also carry `inspect` -
+/// index of an animal whose details should be included in the frame.
+///
+/// # Events
+///
+/// - `{ type: "frame", generation, animals, foods, obstacles,
+///   animal_stride, food_stride, obstacle_stride, inspected? }` follows
+///   each command (except for exports); `animals`, `foods` and `obstacles`
+///   are flat `Float32Array`s laid out like `animals_view()` etc., and
+///   their buffers can be transferred instead of copied,
+/// - `{ type: "generation-finished", statistics }` is reported for each
+///   generation finished while handling a command,
+/// - `{ type: "replay", replay }` answers `export-replay`,
+/// - `{ type: "genome", genome }` answers `export-genome`,
+/// - `{ type: "scenario", params, animals, foods }` precedes the frame
+///   answering `load-scenario`, so that the page can adjust its sliders.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct Worker {
+    simulation: Option<Simulation>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Command {
+    Configure {
+        animals: i32,
+        foods: i32,
+        fov_range: f32,
+        fov_angle: f32,
+        cells: usize,
+        genome: Option<String>,
+    },
+    Step {
+        params: sim::StepParams,
+        #[serde(default = "default_steps")]
+        steps: u32,
+        inspect: Option<usize>,
+    },
+    FastForward {
+        params: sim::StepParams,
+        millis: f64,
+        inspect: Option<usize>,
+    },
+    Snapshot {
+        inspect: Option<usize>,
+    },
+    ExportReplay,
+    ExportGenome {
+        animal: Option<usize>,
+    },
+    Inject {
+        genome: String,
+        inspect: Option<usize>,
+    },
+    LoadScenario {
+        scenario: String,
+    },
+}
+
+fn default_steps() -> u32 {
+    1
+}
+
+#[wasm_bindgen]
+impl Worker {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handles a single command (see above), returning events that should
+    /// be posted back to the page.
+    pub fn handle(&mut self, command: JsValue) -> Result<Array, JsError> {
+        let command: Command = serde_wasm_bindgen::from_value(command)?;
+        let events = Array::new();
+
+        if let Command::Configure {
+            animals,
+            foods,
+            fov_range,
+            fov_angle,
+            cells,
+            genome,
+        } = command
+        {
+            let simulation = match genome {
+                Some(genome) => Simulation::from_genome(&genome, animals, foods)?,
+                None => Simulation::new(animals, foods, fov_range, fov_angle, cells),
+            };
+
+            events.push(&frame(self.simulation.insert(simulation), None)?);
+
+            return Ok(events);
+        }
+
+        if let Command::LoadScenario { scenario } = command {
+            let scenario = sim::Scenario::parse(&scenario)?;
+            let params = serde_wasm_bindgen::to_value(&scenario.params())?;
+            let (sim, rng) = scenario.build();
+
+            events.push(&object(&[
+                ("type", "scenario".into()),
+                ("params", params),
+                ("animals", scenario.population.animals.into()),
+                ("foods", scenario.food.count.into()),
+            ])?);
+
+            let simulation = Simulation::from_sim(rng, sim);
+
+            events.push(&frame(self.simulation.insert(simulation), None)?);
+
+            return Ok(events);
+        }
+
+        let simulation = self
+            .simulation
+            .as_mut()
+            .ok_or_else(|| JsError::new("simulation hasn't been configured yet"))?;
+
+        let (history, inspect) = match command {
+            Command::Configure { .. } | Command::LoadScenario { .. } => unreachable!(),
+
+            Command::Step {
+                params,
+                steps,
+                inspect,
+            } => {
+                let history = (0..steps)
+                    .filter_map(|_| simulation.step_once(&params))
+                    .collect();
+
+                (history, inspect)
+            }
+
+            Command::FastForward {
+                params,
+                millis,
+                inspect,
+            } => (simulation.run_for(millis, &params), inspect),
+
+            Command::Snapshot { inspect } => (Vec::new(), inspect),
+
+            Command::ExportReplay => {
+                events.push(&object(&[
+                    ("type", "replay".into()),
+                    ("replay", simulation.export_replay()?.into()),
+                ])?);
+
+                return Ok(events);
+            }
+
+            Command::ExportGenome { animal } => {
+                let genome = match animal {
+                    Some(idx) => simulation.export_animal(idx)?,
+                    None => simulation.export_best()?,
+                };
+
+                events.push(&object(&[
+                    ("type", "genome".into()),
+                    ("genome", genome.into()),
+                ])?);
+
+                return Ok(events);
+            }
+
+            Command::Inject { genome, inspect } => {
+                simulation.inject(&genome)?;
+
+                (Vec::new(), inspect)
+            }
+        };
+
+        for stats in history {
+            let statistics = serde_wasm_bindgen::to_value(&Statistics::from(&stats))?;
+
+            events.push(&object(&[
+                ("type", "generation-finished".into()),
+                ("statistics", statistics),
+            ])?);
+        }
+
+        events.push(&frame(simulation, inspect)?);
+
+        Ok(events)
+    }
+
+    /// Returns buffers that can be transferred (instead of copied) when
+    /// posting given event.
+    pub fn transferables(event: &JsValue) -> Result<Array, JsError> {
+        let transferables = Array::new();
+
+        for field in ["animals", "foods", "obstacles"] {
+            let value = Reflect::get(event, &field.into()).map_err(js_error)?;
+
+            if let Some(array) = value.dyn_ref::<Float32Array>() {
+                transferables.push(&array.buffer());
+            }
+        }
+
+        Ok(transferables)
+    }
+}
+
+fn frame(simulation: &mut Simulation, inspect: Option<usize>) -> Result<JsValue, JsError> {
+    // Views get invalidated by the next call into the simulation, so we
+    // copy them into fresh buffers which the page can then take over
+    let animals = Float32Array::new(&simulation.animals_view());
+    let foods = Float32Array::new(&simulation.foods_view());
+    let obstacles = Float32Array::new(&simulation.obstacles_view());
+
+    let inspected = match inspect.and_then(|idx| simulation.animal(idx)) {
+        Some(animal) => serde_wasm_bindgen::to_value(&animal)?,
+        None => JsValue::UNDEFINED,
+    };
+
+    object(&[
+        ("type", "frame".into()),
+        ("generation", simulation.get_generation().into()),
+        ("animals", animals.into()),
+        ("foods", foods.into()),
+        ("obstacles", obstacles.into()),
+        ("animal_stride", ANIMAL_STRIDE.into()),
+        ("food_stride", FOOD_STRIDE.into()),
+        ("obstacle_stride", OBSTACLE_STRIDE.into()),
+        ("inspected", inspected),
+    ])
+}
+
+fn object(fields: &[(&str, JsValue)]) -> Result<JsValue, JsError> {
+    let object = Object::new();
+
+    for (key, value) in fields {
+        Reflect::set(&object, &(*key).into(), value).map_err(js_error)?;
+    }
+
+    Ok(object.into())
+}
+
+fn js_error(err: JsValue) -> JsError {
+    JsError::new(&format!("{:?}", err))
+}
@@ -1,20 +1,42 @@
 use crate::*;
 
 //TODO represent rotation and speed as Vector
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Animal {
     pub(crate) position: na::Point2<f32>,
     pub(crate) rotation: na::Rotation2<f32>,
     pub(crate) speed: f32,
     pub(crate) eye: Eye,
     pub(crate) brain: brain::Brain,
-    pub(crate) satiation: usize,
+    /// Total nutrition of everything this bird has eaten in the current
+    /// generation (poison counts negatively)
+    pub(crate) satiation: f32,
+
+    /// Energy left, used only in the steady-state mode (see `Reproduction`)
+    #[serde(default)]
+    pub(crate) energy: f32,
+
+    /// Number of steps this bird has lived through
+    #[serde(default)]
+    pub(crate) age: u32,
+
+    /// Identifier unique within the world (see `World::register()`)
+    #[serde(default)]
+    pub(crate) id: u64,
+
+    /// Identifiers of this bird's parents - none for random birds, one for
+    /// birds crossed over with themselves, two otherwise
+    #[serde(default)]
+    pub(crate) parents: Vec<u64>,
+
+    /// Generation this bird has been born in
+    #[serde(default)]
+    pub(crate) generation: u32,
 }
 
 impl Animal {
-    pub fn random(rng: &mut dyn RngCore, fov_range: f32, fov_angle: f32, cells: usize) -> Self {
-        let eye = Eye::default(fov_range, fov_angle, cells);
-        let brain = Brain::random(rng, &eye);
+    pub fn random(rng: &mut dyn RngCore, eye: Eye, hidden: &[usize]) -> Self {
+        let brain = Brain::random(rng, &eye, hidden);
 
         Self::new(eye, brain, rng)
     }
@@ -22,17 +44,15 @@ impl Animal {
     pub(crate) fn from_chromosome(
         chromosome: ga::Chromosome,
         rng: &mut dyn RngCore,
-        fov_range: f32,
-        fov_angle: f32,
-        cells: usize,
+        eye: Eye,
+        hidden: &[usize],
     ) -> Self {
-        let eye = Eye::default(fov_range, fov_angle, cells);
-        let brain = Brain::from_chromosome(chromosome, &eye);
+        let brain = Brain::from_chromosome(chromosome, &eye, hidden);
 
         Self::new(eye, brain, rng)
     }
 
-    pub(crate) fn as_chromosome(&self) -> ga::Chromosome {
+    pub fn as_chromosome(&self) -> ga::Chromosome {
         // We evolve only our birds' brains, but technically there's no
         // reason not to simulate e.g. physical properties such as size.
         //
@@ -50,7 +70,12 @@ impl Animal {
             speed: 0.002,
             eye,
             brain,
-            satiation: 0,
+            satiation: 0.0,
+            energy: 0.0,
+            age: 0,
+            id: 0,
+            parents: Vec::new(),
+            generation: 0,
         }
     }
 
@@ -61,4 +86,49 @@ impl Animal {
     pub fn rotation(&self) -> na::Rotation2<f32> {
         self.rotation
     }
+
+    pub fn satiation(&self) -> f32 {
+        self.satiation
+    }
+
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+
+    pub fn age(&self) -> u32 {
+        self.age
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn parents(&self) -> &[u64] {
+        &self.parents
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn eye(&self) -> &Eye {
+        &self.eye
+    }
+
+    pub fn brain(&self) -> &Brain {
+        &self.brain
+    }
+
+    /// What the bird sees right now - one value per eye cell (and food
+    /// kind the eye can tell apart), the closer the food, the higher the
+    /// value.
+    pub fn vision(&self, foods: &[Food]) -> Vec<f32> {
+        self.eye.process_vision(self.position, self.rotation, foods)
+    }
+
+    /// Outputs of each layer of bird's brain for given vision: the hidden
+    /// layer first, then speed & rotation.
+    pub fn activations(&self, vision: Vec<f32>) -> Vec<Vec<f32>> {
+        self.brain.nn.activations(vision)
+    }
 }
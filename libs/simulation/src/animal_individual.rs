@@ -1,7 +1,7 @@
 use crate::*;
 
 pub struct AnimalIndividual {
-    fitness: f32,
+    pub(crate) fitness: f32,
     chromosome: ga::Chromosome,
 }
 
@@ -25,13 +25,14 @@ impl ga::Individual for AnimalIndividual {
 impl AnimalIndividual {
     pub fn from_animal(animal: &Animal) -> Self {
         Self {
-            fitness: animal.satiation as f32,
-            chromosome: animal.as_chromosome(), 
+            // (roulette can't deal with negative fitness, which birds
+            // that have eaten mostly poison would otherwise get)
+            fitness: animal.satiation.max(0.0),
+            chromosome: animal.as_chromosome(),
         }
-        
     }
 
-    pub fn into_animal(self, rng: &mut dyn RngCore, fov_range: f32, fov_angle: f32, cells: usize) -> Animal {
-        Animal::from_chromosome(self.chromosome, rng, fov_range, fov_angle, cells)
+    pub fn into_animal(self, rng: &mut dyn RngCore, eye: Eye, hidden: &[usize]) -> Animal {
+        Animal::from_chromosome(self.chromosome, rng, eye, hidden)
     }
 }
@@ -0,0 +1,217 @@
+use crate::*;
+
+/// How many times we try to find a free spot before giving up and placing
+/// something on top of an obstacle.
+const MAX_PLACEMENT_ATTEMPTS: usize = 32;
+
+/// Where the birds live: a `width` x `height` rectangle, possibly with some
+/// obstacles inside.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Arena {
+    pub width: f32,
+    pub height: f32,
+    pub boundary: Boundary,
+    pub obstacles: Vec<Obstacle>,
+}
+
+/// What happens to a bird that reaches the edge of the arena.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Boundary {
+    /// Bird appears on the opposite side
+    Wrap,
+
+    /// Bird bounces off, like a ball
+    Bounce,
+}
+
+/// Circular obstacle that birds can't fly through; birds can't see them
+/// either, so they have to learn to avoid them the hard way.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Obstacle {
+    pub position: na::Point2<f32>,
+    pub radius: f32,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            height: 1.0,
+            boundary: Boundary::Wrap,
+            obstacles: Vec::new(),
+        }
+    }
+}
+
+impl Arena {
+    /// Returns random position that's not covered by any obstacle.
+    pub(crate) fn spawn(&self, rng: &mut dyn RngCore) -> na::Point2<f32> {
+        let position = rng.gen();
+
+        self.place(rng, position)
+    }
+
+    /// Scales position drawn from the unit square to the arena, drawing
+    /// it again if it falls inside an obstacle.
+    pub(crate) fn place(
+        &self,
+        rng: &mut dyn RngCore,
+        mut position: na::Point2<f32>,
+    ) -> na::Point2<f32> {
+        for _ in 0..MAX_PLACEMENT_ATTEMPTS {
+            let scaled = self.scale(position);
+
+            if !self.is_blocked(scaled) {
+                return scaled;
+            }
+
+            position = rng.gen();
+        }
+
+        self.scale(position)
+    }
+
+    /// Draws position from given distribution until it falls inside the
+    /// arena and outside of obstacles, falling back to `spawn()`.
+    pub(crate) fn settle(
+        &self,
+        rng: &mut dyn RngCore,
+        mut sample: impl FnMut(&mut dyn RngCore) -> na::Point2<f32>,
+    ) -> na::Point2<f32> {
+        for _ in 0..MAX_PLACEMENT_ATTEMPTS {
+            let position = sample(rng);
+
+            let inside = (0.0..=self.width).contains(&position.x)
+                && (0.0..=self.height).contains(&position.y);
+
+            if inside && !self.is_blocked(position) {
+                return position;
+            }
+        }
+
+        self.spawn(rng)
+    }
+
+    /// Keeps a bird that has just moved inside the arena and outside of
+    /// obstacles.
+    pub(crate) fn confine(
+        &self,
+        position: &mut na::Point2<f32>,
+        rotation: &mut na::Rotation2<f32>,
+    ) {
+        match self.boundary {
+            Boundary::Wrap => {
+                position.x = na::wrap(position.x, 0.0, self.width);
+                position.y = na::wrap(position.y, 0.0, self.height);
+            }
+
+            Boundary::Bounce => {
+                // Bird flies towards `(-sin, cos)` of its rotation, so
+                // bouncing off a vertical wall negates the angle, while
+                // bouncing off a horizontal one mirrors it around PI / 2
+                if position.x < 0.0 || position.x > self.width {
+                    position.x = position.x.clamp(0.0, self.width);
+                    *rotation = na::Rotation2::new(-rotation.angle());
+                }
+
+                if position.y < 0.0 || position.y > self.height {
+                    position.y = position.y.clamp(0.0, self.height);
+                    *rotation = na::Rotation2::new(std::f32::consts::PI - rotation.angle());
+                }
+            }
+        }
+
+        for obstacle in &self.obstacles {
+            let offset = *position - obstacle.position;
+            let distance = offset.norm();
+
+            if distance < obstacle.radius {
+                let direction = if distance > 0.0 {
+                    offset / distance
+                } else {
+                    na::Vector2::y()
+                };
+
+                *position = obstacle.position + direction * obstacle.radius;
+            }
+        }
+    }
+
+    fn scale(&self, position: na::Point2<f32>) -> na::Point2<f32> {
+        na::Point2::new(position.x * self.width, position.y * self.height)
+    }
+
+    fn is_blocked(&self, position: na::Point2<f32>) -> bool {
+        self.obstacles
+            .iter()
+            .any(|obstacle| na::distance(&position, &obstacle.position) < obstacle.radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn arena(boundary: Boundary) -> Arena {
+        Arena {
+            width: 2.0,
+            height: 1.0,
+            boundary,
+            obstacles: vec![Obstacle {
+                position: na::Point2::new(1.0, 0.5),
+                radius: 0.25,
+            }],
+        }
+    }
+
+    #[test]
+    fn spawns_outside_of_obstacles() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let arena = arena(Boundary::Wrap);
+
+        for _ in 0..1000 {
+            let position = arena.spawn(&mut rng);
+
+            assert!((0.0..=2.0).contains(&position.x));
+            assert!((0.0..=1.0).contains(&position.y));
+            assert!(!arena.is_blocked(position));
+        }
+    }
+
+    #[test]
+    fn wraps() {
+        let mut position = na::Point2::new(2.1, -0.1);
+        let mut rotation = na::Rotation2::new(FRAC_PI_2);
+
+        arena(Boundary::Wrap).confine(&mut position, &mut rotation);
+
+        approx::assert_relative_eq!(position, na::Point2::new(0.1, 0.9), epsilon = 1e-6);
+        approx::assert_relative_eq!(rotation.angle(), FRAC_PI_2);
+    }
+
+    #[test]
+    fn bounces() {
+        let mut position = na::Point2::new(2.1, 0.2);
+        let mut rotation = na::Rotation2::new(-FRAC_PI_2);
+
+        arena(Boundary::Bounce).confine(&mut position, &mut rotation);
+
+        approx::assert_relative_eq!(position, na::Point2::new(2.0, 0.2));
+        approx::assert_relative_eq!(rotation.angle(), FRAC_PI_2);
+    }
+
+    #[test]
+    fn pushes_out_of_obstacles() {
+        let mut position = na::Point2::new(1.1, 0.5);
+        let mut rotation = na::Rotation2::identity();
+
+        arena(Boundary::Wrap).confine(&mut position, &mut rotation);
+
+        approx::assert_relative_eq!(position, na::Point2::new(1.25, 0.5));
+    }
+}
@@ -1,26 +1,20 @@
 use crate::*;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Brain {
     pub(crate) nn: nn::Network,
 }
 
 impl Brain {
-    pub fn random(rng: &mut dyn RngCore, eye: &Eye) -> Self {
+    pub fn random(rng: &mut dyn RngCore, eye: &Eye, hidden: &[usize]) -> Self {
         Self {
-            nn: nn::Network::random(rng, &Self::topology(eye)),
+            nn: nn::Network::random(rng, &Self::topology(eye, hidden)),
         }
     }
 
-    pub(crate) fn from_chromosome(
-        chromosome: ga::Chromosome,
-        eye: &Eye,
-    ) -> Self {
+    pub(crate) fn from_chromosome(chromosome: ga::Chromosome, eye: &Eye, hidden: &[usize]) -> Self {
         Self {
-            nn: nn::Network::from_weights(
-                &Self::topology(eye),
-                chromosome,
-            ),
+            nn: nn::Network::from_weights(&Self::topology(eye, hidden), chromosome),
         }
     }
 
@@ -28,15 +22,32 @@ impl Brain {
         self.nn.weights().collect()
     }
 
-    fn topology(eye: &Eye) -> [nn::LayerTopology; 3] {
-        [
-            nn::LayerTopology {
-                neurons: eye.cells(),
-            },
-            nn::LayerTopology {
-                neurons: 2 * eye.cells(),
-            },
-            nn::LayerTopology { neurons: 2 },
-        ]
+    /// Sizes of the hidden layers this brain has been created with
+    pub fn hidden(&self) -> Vec<usize> {
+        let topology = self.nn.topology();
+
+        topology[1..topology.len() - 1]
+            .iter()
+            .map(|layer| layer.neurons)
+            .collect()
+    }
+
+    /// Hidden layers used when nothing else is specified: a single one,
+    /// twice as large as the eye.
+    pub fn default_hidden(eye: &Eye) -> Vec<usize> {
+        vec![2 * eye.cells()]
+    }
+
+    pub(crate) fn topology(eye: &Eye, hidden: &[usize]) -> Vec<nn::LayerTopology> {
+        let inputs = nn::LayerTopology {
+            neurons: eye.inputs(),
+        };
+
+        let outputs = nn::LayerTopology { neurons: 2 };
+
+        std::iter::once(inputs)
+            .chain(hidden.iter().map(|&neurons| nn::LayerTopology { neurons }))
+            .chain(std::iter::once(outputs))
+            .collect()
     }
 }
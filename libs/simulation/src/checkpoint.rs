@@ -0,0 +1,399 @@
+use crate::*;
+use std::{fmt, io};
+
+/// Version of the checkpoint format; bumped whenever a change to any of
+/// the serialized structs makes older checkpoints unreadable (see
+/// `migrate_v1()` and `migrate_v2()` for older versions that can still be
+/// loaded).
+pub const CHECKPOINT_VERSION: u32 = 3;
+
+#[derive(Serialize)]
+struct CheckpointRef<'a> {
+    version: u32,
+    simulation: &'a Simulation,
+    rng: &'a ChaCha8Rng,
+}
+
+#[derive(Deserialize)]
+struct Checkpoint {
+    simulation: Simulation,
+    rng: ChaCha8Rng,
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+    MissingVersion,
+    InvalidGenome(&'static str),
+    InvalidOptimizer(&'static str),
+    InvalidReplay(&'static str),
+}
+
+impl Simulation {
+    /// Saves everything needed to continue this simulation later - the
+    /// world, the optimizer's state, history and the random number
+    /// generator driving it all - as JSON.
+    pub fn save_checkpoint(
+        &self,
+        rng: &ChaCha8Rng,
+        writer: impl io::Write,
+    ) -> Result<(), CheckpointError> {
+        let checkpoint = CheckpointRef {
+            version: CHECKPOINT_VERSION,
+            simulation: self,
+            rng,
+        };
+
+        serde_json::to_writer(writer, &checkpoint)?;
+
+        Ok(())
+    }
+
+    /// Restores simulation saved with `save_checkpoint()`; stepping the
+    /// returned simulation with the returned generator continues exactly
+    /// where the original one has left off.
+    pub fn load_checkpoint(reader: impl io::Read) -> Result<(Self, ChaCha8Rng), CheckpointError> {
+        let (version, mut document) = read_document(reader)?;
+
+        if version == 0 || version > CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+
+        if version < 2 {
+            migrate_v1(&mut document);
+        }
+
+        if version < 3 {
+            migrate_v2(&mut document);
+        }
+
+        let mut checkpoint: Checkpoint = serde_json::from_value(document)?;
+
+        checkpoint.simulation.optimizer().validate()?;
+
+        if checkpoint.simulation.reproduction().is_some() {
+            checkpoint.simulation.optimizer().validate_steady_state()?;
+        }
+
+        checkpoint.simulation.world.restore_ids();
+
+        Ok((checkpoint.simulation, checkpoint.rng))
+    }
+}
+
+/// Reads JSON document with a top-level `version` field, making sure the
+/// version matches before trying to make sense of the rest of it.
+pub(crate) fn read_versioned<T>(reader: impl io::Read, expected: u32) -> Result<T, CheckpointError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let (version, document) = read_document(reader)?;
+
+    if version != expected {
+        return Err(CheckpointError::UnsupportedVersion(version));
+    }
+
+    Ok(serde_json::from_value(document)?)
+}
+
+fn read_document(reader: impl io::Read) -> Result<(u32, serde_json::Value), CheckpointError> {
+    let document: serde_json::Value = serde_json::from_reader(reader)?;
+
+    let version = document
+        .get("version")
+        .and_then(|version| version.as_u64())
+        .ok_or(CheckpointError::MissingVersion)?;
+
+    Ok((version as u32, document))
+}
+
+/// Upgrades checkpoint saved before food could drift, rot and regrow - its
+/// world gets a fixed amount of uniformly spawned food, which is how food
+/// used to behave back then.
+fn migrate_v1(document: &mut serde_json::Value) {
+    let Some(world) = document
+        .pointer_mut("/simulation/world")
+        .and_then(|world| world.as_object_mut())
+    else {
+        return;
+    };
+
+    let Some(foods) = world
+        .get_mut("foods")
+        .and_then(|foods| foods.as_array_mut())
+    else {
+        return;
+    };
+
+    for food in foods.iter_mut().filter_map(|food| food.as_object_mut()) {
+        food.insert(
+            "rotation".into(),
+            serde_json::json!(na::Rotation2::<f32>::identity()),
+        );
+        food.insert("age".into(), serde_json::json!(0));
+    }
+
+    let config = FoodConfig {
+        count: foods.len(),
+        ..Default::default()
+    };
+
+    world.insert(
+        "food".into(),
+        serde_json::json!({ "config": config, "sites": [], "step": 0 }),
+    );
+}
+
+/// Upgrades checkpoint saved when the steady-state mode had mutation
+/// settings of its own - children are now bred with the optimizer's ones.
+fn migrate_v2(document: &mut serde_json::Value) {
+    let Some(reproduction) = document
+        .pointer_mut("/simulation/reproduction")
+        .and_then(|reproduction| reproduction.as_object_mut())
+    else {
+        return;
+    };
+
+    reproduction.remove("mutation_chance");
+    reproduction.remove("mutation_coeff");
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "couldn't access checkpoint: {}", err),
+            Self::Json(err) => write!(f, "checkpoint is malformed: {}", err),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "checkpoint has unsupported version {}", version)
+            }
+            Self::MissingVersion => write!(f, "checkpoint has no version"),
+            Self::InvalidGenome(reason) => write!(f, "genome is invalid: {}", reason),
+            Self::InvalidOptimizer(reason) => write!(f, "optimizer is invalid: {}", reason),
+            Self::InvalidReplay(reason) => write!(f, "replay is invalid: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+impl From<io::Error> for CheckpointError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for CheckpointError {
+    fn from(err: serde_json::Error) -> Self {
+        if err.is_io() {
+            Self::Io(err.into())
+        } else {
+            Self::Json(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn step(sim: &mut Simulation, rng: &mut ChaCha8Rng, steps: usize) {
+        for _ in 0..steps {
+            sim.step(
+                rng,
+                &StepParams {
+                    generation_length: 100,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    fn save(sim: &Simulation, rng: &ChaCha8Rng) -> Vec<u8> {
+        let mut checkpoint = Vec::new();
+        sim.save_checkpoint(rng, &mut checkpoint).unwrap();
+        checkpoint
+    }
+
+    #[test]
+    fn resumes_identically() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9);
+
+        step(&mut sim, &mut rng, 150);
+
+        let (mut restored, mut restored_rng) =
+            Simulation::load_checkpoint(save(&sim, &rng).as_slice()).unwrap();
+
+        // Go through another generation change, so that both the optimizer
+        // and the generator get involved
+        step(&mut sim, &mut rng, 150);
+        step(&mut restored, &mut restored_rng, 150);
+
+        assert_eq!(sim.generation, 2);
+        assert_eq!(sim.history().len(), 2);
+        assert_eq!(save(&sim, &rng), save(&restored, &restored_rng));
+    }
+
+    #[test]
+    fn resumes_identically_with_cma_es() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let mut sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9)
+            .with_optimizer(Optimizer::CmaEs(ga::CmaEs::new(0.5)));
+
+        step(&mut sim, &mut rng, 150);
+
+        let (mut restored, mut restored_rng) =
+            Simulation::load_checkpoint(save(&sim, &rng).as_slice()).unwrap();
+
+        step(&mut sim, &mut rng, 150);
+        step(&mut restored, &mut restored_rng, 150);
+
+        assert_eq!(save(&sim, &rng), save(&restored, &restored_rng));
+    }
+
+    #[test]
+    fn loads_older_checkpoints() {
+        // Saved before food kinds, meals, lineage and diversity have been
+        // introduced
+        let checkpoint = include_str!("../fixtures/checkpoint-v2.json");
+
+        let (mut sim, mut rng) = Simulation::load_checkpoint(checkpoint.as_bytes()).unwrap();
+
+        assert_eq!(sim.history().len(), 1);
+        assert!(sim.history()[0].meals.is_empty());
+        assert!(sim.world().foods().iter().all(|food| food.kind() == 0));
+
+        let ids: Vec<_> = sim.world().animals().iter().map(Animal::id).collect();
+        assert_eq!(ids, [0, 1, 2]);
+
+        // Make sure somebody has eaten, so that there's something to select
+        sim.world.animals[0].satiation = 1.0;
+        for _ in 0..150 {
+            sim.step(
+                &mut rng,
+                &StepParams {
+                    generation_length: 100,
+                    cells: 3,
+                    ..Default::default()
+                },
+            );
+        }
+
+        assert_eq!(sim.history().len(), 2);
+        assert!(sim.world().animals().iter().all(|animal| animal.id() >= 3));
+    }
+
+    #[test]
+    fn rejects_invalid_optimizer() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9);
+
+        let checkpoint: serde_json::Value = serde_json::from_slice(&save(&sim, &rng)).unwrap();
+
+        for (field, value) in [
+            (
+                "bounds",
+                serde_json::json!({ "ranges": [], "repair": "clamp" }),
+            ),
+            (
+                "bounds",
+                serde_json::json!({ "ranges": [[1.0, -1.0]], "repair": "clamp" }),
+            ),
+            (
+                "scaling",
+                serde_json::json!({ "kind": "sigma-truncation", "c": 0.0 }),
+            ),
+            ("immigrants", serde_json::json!(1.5)),
+            (
+                "restart",
+                serde_json::json!({ "stagnation": 0, "fraction": 1.0, "elites": 0 }),
+            ),
+        ] {
+            let mut checkpoint = checkpoint.clone();
+
+            checkpoint["simulation"]["optimizer"]["GeneticAlgorithm"][field] = value;
+
+            assert!(matches!(
+                Simulation::load_checkpoint(checkpoint.to_string().as_bytes()),
+                Err(CheckpointError::InvalidOptimizer(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn loads_version_1_checkpoints() {
+        // Saved before food could drift, rot and regrow
+        let checkpoint = include_str!("../fixtures/checkpoint-v1.json");
+
+        let (mut sim, mut rng) = Simulation::load_checkpoint(checkpoint.as_bytes()).unwrap();
+
+        assert_eq!(sim.world().foods().len(), 5);
+        assert_eq!(sim.world().food().config().count, 5);
+        assert_eq!(sim.world().food().config().spawn, FoodSpawn::Uniform);
+
+        sim.world.animals[0].satiation = 1.0;
+        for _ in 0..150 {
+            sim.step(
+                &mut rng,
+                &StepParams {
+                    generation_length: 100,
+                    cells: 3,
+                    ..Default::default()
+                },
+            );
+        }
+
+        assert_eq!(sim.history().len(), 2);
+        assert_eq!(sim.world().foods().len(), 5);
+    }
+
+    #[test]
+    fn loads_version_2_steady_state_checkpoints() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9)
+            .with_reproduction(Reproduction::default());
+
+        let mut checkpoint: serde_json::Value = serde_json::from_slice(&save(&sim, &rng)).unwrap();
+
+        checkpoint["version"] = serde_json::json!(2);
+        checkpoint["simulation"]["reproduction"]["mutation_chance"] = serde_json::json!(0.01);
+        checkpoint["simulation"]["reproduction"]["mutation_coeff"] = serde_json::json!(0.3);
+
+        let (restored, _) = Simulation::load_checkpoint(checkpoint.to_string().as_bytes()).unwrap();
+
+        assert!(restored.reproduction().is_some());
+    }
+
+    #[test]
+    fn rejects_immigrants_in_steady_state() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9)
+            .with_reproduction(Reproduction::default());
+
+        let mut checkpoint: serde_json::Value = serde_json::from_slice(&save(&sim, &rng)).unwrap();
+
+        checkpoint["simulation"]["optimizer"]["GeneticAlgorithm"]["immigrants"] =
+            serde_json::json!(0.1);
+
+        assert!(matches!(
+            Simulation::load_checkpoint(checkpoint.to_string().as_bytes()),
+            Err(CheckpointError::InvalidOptimizer(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unsupported_version() {
+        let checkpoint = r#"{ "version": 999, "simulation": null, "rng": null }"#;
+
+        assert!(matches!(
+            Simulation::load_checkpoint(checkpoint.as_bytes()),
+            Err(CheckpointError::UnsupportedVersion(999))
+        ));
+    }
+}
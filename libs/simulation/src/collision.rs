@@ -0,0 +1,237 @@
+use crate::*;
+
+/// How close a bird has to get to food in order to eat it.
+const EATING_DISTANCE: f32 = 0.01;
+
+/// How birds interact with each other when they meet - by default they
+/// simply pass through each other.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Collisions {
+    /// Radius of birds' bodies; when set, birds that overlap get pushed
+    /// apart
+    pub body_radius: Option<f32>,
+
+    pub contention: Contention,
+}
+
+/// Who gets food that's within reach of a few birds at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Contention {
+    /// Whichever bird happens to be processed first (cheapest, but favours
+    /// birds at the beginning of the population)
+    FirstCome,
+
+    /// The closest bird
+    Nearest,
+
+    /// All of them, in equal parts
+    Split,
+}
+
+impl Default for Collisions {
+    fn default() -> Self {
+        Self {
+            body_radius: None,
+            contention: Contention::FirstCome,
+        }
+    }
+}
+
+impl World {
+    /// Lets birds eat the food they've reached.
+    pub(crate) fn feed_animals(&mut self, rng: &mut dyn RngCore) {
+        let mut meal = Meal {
+            present: self.foods.len(),
+            eaten: vec![false; self.foods.len()],
+        };
+
+        if self.collisions.contention == Contention::FirstCome {
+            for animal in 0..self.animals.len() {
+                for food in 0..self.foods.len() {
+                    if !meal.eaten[food] && self.distance(animal, food) <= EATING_DISTANCE {
+                        self.feed(rng, &[animal], food, &mut meal);
+                    }
+                }
+            }
+        } else {
+            for food in 0..self.foods.len() {
+                let mut eaters: Vec<_> = (0..self.animals.len())
+                    .filter(|&animal| self.distance(animal, food) <= EATING_DISTANCE)
+                    .collect();
+
+                if eaters.is_empty() {
+                    continue;
+                }
+
+                if self.collisions.contention == Contention::Nearest {
+                    let nearest = eaters
+                        .iter()
+                        .copied()
+                        .min_by(|&a, &b| self.distance(a, food).total_cmp(&self.distance(b, food)))
+                        .unwrap();
+
+                    eaters = vec![nearest];
+                }
+
+                self.feed(rng, &eaters, food, &mut meal);
+            }
+        }
+
+        if meal.present < self.foods.len() {
+            let mut eaten = meal.eaten.into_iter();
+            self.foods.retain(|_| !eaten.next().unwrap());
+        }
+    }
+
+    /// Pushes overlapping birds away from each other.
+    pub(crate) fn separate_animals(&mut self) {
+        let Some(radius) = self.collisions.body_radius else {
+            return;
+        };
+
+        for a in 0..self.animals.len() {
+            for b in (a + 1)..self.animals.len() {
+                let offset = self.animals[b].position - self.animals[a].position;
+                let distance = offset.norm();
+                let overlap = 2.0 * radius - distance;
+
+                if overlap <= 0.0 {
+                    continue;
+                }
+
+                let direction = if distance > 0.0 {
+                    offset / distance
+                } else {
+                    na::Vector2::y()
+                };
+
+                self.animals[a].position -= direction * overlap / 2.0;
+                self.animals[b].position += direction * overlap / 2.0;
+            }
+        }
+
+        for animal in &mut self.animals {
+            self.arena
+                .confine(&mut animal.position, &mut animal.rotation);
+        }
+    }
+
+    fn distance(&self, animal: usize, food: usize) -> f32 {
+        na::distance(&self.animals[animal].position, &self.foods[food].position)
+    }
+
+    /// Gives given food to given birds, splitting it equally.
+    fn feed(&mut self, rng: &mut dyn RngCore, eaters: &[usize], food: usize, meal: &mut Meal) {
+        let nutrition = self.food.eat(&self.foods[food]) / eaters.len() as f32;
+
+        for &animal in eaters {
+            self.animals[animal].satiation += nutrition;
+            self.animals[animal].energy += nutrition;
+        }
+
+        // Eaten food usually appears somewhere else right away, unless it's
+        // winter or its patch has been depleted
+        match self.food.respawn(rng, &self.arena, meal.present) {
+            Some(new_food) => self.foods[food] = new_food,
+            None => {
+                meal.eaten[food] = true;
+                meal.present -= 1;
+            }
+        }
+    }
+}
+
+/// Food eaten during a single step.
+struct Meal {
+    /// How many pieces of food are left
+    present: usize,
+
+    /// Which pieces have been eaten without being replaced
+    eaten: Vec<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn world(rng: &mut ChaCha8Rng, contention: Contention) -> World {
+        let eye = Eye::default(0.25, 3.9, 9);
+
+        let mut world = World::populate(
+            rng,
+            Arena::default(),
+            FoodConfig {
+                count: 1,
+                ..Default::default()
+            },
+            2,
+            &eye,
+            &Brain::default_hidden(&eye),
+        )
+        .with_collisions(Collisions {
+            body_radius: Some(0.01),
+            contention,
+        });
+
+        world.foods[0].position = na::Point2::new(0.5, 0.5);
+        world.animals[0].position = na::Point2::new(0.5, 0.509);
+        world.animals[1].position = na::Point2::new(0.5, 0.495);
+        world
+    }
+
+    #[test]
+    fn first_come() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = world(&mut rng, Contention::FirstCome);
+
+        world.feed_animals(&mut rng);
+
+        assert_eq!(world.animals[0].satiation, 1.0);
+        assert_eq!(world.animals[1].satiation, 0.0);
+    }
+
+    #[test]
+    fn nearest() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = world(&mut rng, Contention::Nearest);
+
+        world.feed_animals(&mut rng);
+
+        assert_eq!(world.animals[0].satiation, 0.0);
+        assert_eq!(world.animals[1].satiation, 1.0);
+    }
+
+    #[test]
+    fn split() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = world(&mut rng, Contention::Split);
+
+        world.feed_animals(&mut rng);
+
+        assert_eq!(world.animals[0].satiation, 0.5);
+        assert_eq!(world.animals[1].satiation, 0.5);
+    }
+
+    #[test]
+    fn separates() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = world(&mut rng, Contention::FirstCome);
+
+        world.separate_animals();
+
+        approx::assert_relative_eq!(
+            world.animals[0].position,
+            na::Point2::new(0.5, 0.512),
+            epsilon = 1e-6
+        );
+
+        approx::assert_relative_eq!(
+            world.animals[1].position,
+            na::Point2::new(0.5, 0.492),
+            epsilon = 1e-6
+        );
+    }
+}
@@ -2,11 +2,20 @@ use crate::*;
 use std::f32::consts::*;
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Eye {
     fov_range: f32,
     fov_angle: f32,
     cells: usize,
+
+    /// Number of food kinds this eye can tell apart; each cell sees each
+    /// kind separately
+    #[serde(default = "default_channels")]
+    channels: usize,
+}
+
+fn default_channels() -> usize {
+    1
 }
 
 impl Eye {
@@ -18,20 +27,45 @@ impl Eye {
         assert!(fov_angle > 0.0);
         assert!(cells > 0);
 
-        Self { fov_range, fov_angle, cells }
+        Self { fov_range, fov_angle, cells, channels: 1 }
+    }
+
+    /// Makes this eye see each of given number of food kinds separately.
+    pub fn with_channels(mut self, channels: usize) -> Self {
+        assert!(channels > 0);
+
+        self.channels = channels;
+        self
+    }
+
+    pub fn fov_range(&self) -> f32 {
+        self.fov_range
+    }
+
+    pub fn fov_angle(&self) -> f32 {
+        self.fov_angle
     }
 
     pub fn cells(&self) -> usize {
         self.cells
     }
 
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Length of the vision this eye produces: `channels` values per cell
+    pub fn inputs(&self) -> usize {
+        self.cells * self.channels
+    }
+
     pub(crate) fn process_vision(
         &self,
         position: na::Point2<f32>,
         rotation: na::Rotation2<f32>,
         foods: &[Food],
     ) -> Vec<f32> {
-        let mut cells = vec![0.0; self.cells];
+        let mut cells = vec![0.0; self.inputs()];
 
         for food in foods {
             let vec = food.position - position;
@@ -51,9 +85,12 @@ impl Eye {
 
             let angle = angle + self.fov_angle / 2.0;
             let cell = angle / self.fov_angle * (self.cells as f32);
-            let cell = (cell as usize).min(cells.len() - 1);
+            let cell = (cell as usize).min(self.cells - 1);
 
-            cells[cell] += (self.fov_range - dist) / self.fov_range;
+            // Kinds the eye can't tell apart all look like the last one
+            let channel = food.kind.min(self.channels - 1);
+
+            cells[cell * self.channels + channel] += (self.fov_range - dist) / self.fov_range;
         }
 
         cells
@@ -63,3 +100,33 @@ impl Eye {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels() {
+        let eye = Eye::new(1.0, FRAC_PI_2, 2).with_channels(3);
+
+        let food = |x, y, kind| Food {
+            kind,
+            ..Food::new(na::Point2::new(x, y))
+        };
+
+        // Bird looks towards +y; angles grow counter-clockwise, so food
+        // at -x lands in the second cell, and food at +x in the first one
+        let vision = eye.process_vision(
+            na::Point2::new(0.5, 0.5),
+            na::Rotation2::identity(),
+            &[food(0.4, 1.0, 0), food(0.6, 1.0, 1), food(0.6, 1.0, 5)],
+        );
+
+        // (kinds the eye can't tell apart look like the last one it can)
+        assert_eq!(vision.len(), 6);
+        assert_eq!(vision[0], 0.0);
+        assert!(vision[1] > 0.0);
+        assert!(vision[2] > 0.0);
+        assert!(vision[3] > 0.0);
+        assert_eq!(&vision[4..], [0.0, 0.0]);
+    }
+}
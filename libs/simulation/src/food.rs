@@ -1,18 +1,39 @@
 use crate::*;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Food {
     pub(crate) position: na::Point2<f32>,
+
+    /// Direction this food drifts in (see `FoodConfig::speed`)
+    pub(crate) rotation: na::Rotation2<f32>,
+
+    /// Number of steps since this food has appeared
+    pub(crate) age: u32,
+
+    /// Index into `FoodConfig::kinds`
+    #[serde(default)]
+    pub(crate) kind: usize,
 }
 
 impl Food {
     pub fn random(rng: &mut dyn RngCore) -> Self {
+        Self::new(rng.gen())
+    }
+
+    pub(crate) fn new(position: na::Point2<f32>) -> Self {
         Self {
-            position: rng.gen()
+            position,
+            rotation: na::Rotation2::identity(),
+            age: 0,
+            kind: 0,
         }
-    }    
+    }
 
     pub fn position(&self) -> na::Point2<f32> {
         self.position
     }
+
+    pub fn kind(&self) -> usize {
+        self.kind
+    }
 }
@@ -0,0 +1,491 @@
+use crate::*;
+use rand_distr::StandardNormal;
+use std::f32::consts::TAU;
+
+/// How food behaves: where it appears, whether it moves, rots and so on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FoodConfig {
+    /// How many pieces of food there are at once (at most, when `seasons`
+    /// are enabled or `spawn` runs out of food)
+    pub count: usize,
+
+    /// Kinds of food there are; each one is seen by a separate channel of
+    /// birds' eyes
+    pub kinds: Vec<FoodKind>,
+
+    pub spawn: FoodSpawn,
+
+    /// How far each piece of food drifts per step (0.0 = it stays put)
+    pub speed: f32,
+
+    /// Number of steps after which uneaten food rots away (and a new one
+    /// appears somewhere else)
+    pub lifetime: Option<u32>,
+
+    pub seasons: Option<Seasons>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FoodKind {
+    /// How much eating this kind adds to bird's satiation; negative for
+    /// poison
+    pub nutrition: f32,
+
+    /// How much of the food is of this kind, relative to other kinds
+    pub ratio: f32,
+}
+
+/// Where new food appears.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum FoodSpawn {
+    /// Anywhere in the arena
+    Uniform,
+
+    /// Around a few randomly placed centers, with normal distribution of
+    /// given standard deviation
+    Clusters { clusters: usize, spread: f32 },
+
+    /// Inside a few randomly placed circular patches; each patch holds a
+    /// limited stock of food that gets depleted as food appears in it and
+    /// regrows by `regrowth` per step, up to `capacity`
+    Patches {
+        patches: usize,
+        radius: f32,
+        capacity: f32,
+        regrowth: f32,
+    },
+}
+
+/// Cycle of plenty and scarcity: over each `length` steps, amount of food
+/// drops to `1.0 - amplitude` of `FoodConfig::count` and comes back again.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Seasons {
+    pub length: u32,
+    pub amplitude: f32,
+}
+
+/// `FoodConfig` together with the state of the environment it describes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FoodSupply {
+    config: FoodConfig,
+
+    /// Centers of clusters or patches (depending on the spawn strategy)
+    sites: Vec<Site>,
+
+    step: u64,
+
+    /// How many pieces of each kind have been eaten in the current
+    /// generation (empty in checkpoints saved before meals were counted)
+    #[serde(default)]
+    meals: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Site {
+    position: na::Point2<f32>,
+
+    /// How much food this patch has left
+    stock: f32,
+}
+
+impl Default for FoodConfig {
+    fn default() -> Self {
+        Self {
+            count: 40,
+            kinds: vec![FoodKind::default()],
+            spawn: FoodSpawn::Uniform,
+            speed: 0.0,
+            lifetime: None,
+            seasons: None,
+        }
+    }
+}
+
+impl Default for FoodKind {
+    fn default() -> Self {
+        Self {
+            nutrition: 1.0,
+            ratio: 1.0,
+        }
+    }
+}
+
+impl FoodSupply {
+    pub fn new(rng: &mut dyn RngCore, arena: &Arena, config: FoodConfig) -> Self {
+        assert!(!config.kinds.is_empty());
+
+        let sites = match config.spawn {
+            FoodSpawn::Uniform => Vec::new(),
+
+            FoodSpawn::Clusters { clusters, .. } => {
+                assert!(clusters > 0);
+
+                (0..clusters)
+                    .map(|_| Site {
+                        position: arena.spawn(rng),
+                        stock: 0.0,
+                    })
+                    .collect()
+            }
+
+            FoodSpawn::Patches {
+                patches, capacity, ..
+            } => {
+                assert!(patches > 0);
+
+                (0..patches)
+                    .map(|_| Site {
+                        position: arena.spawn(rng),
+                        stock: capacity,
+                    })
+                    .collect()
+            }
+        };
+
+        Self {
+            meals: vec![0; config.kinds.len()],
+            config,
+            sites,
+            step: 0,
+        }
+    }
+
+    pub fn config(&self) -> &FoodConfig {
+        &self.config
+    }
+
+    /// How many pieces of food there should be right now
+    pub fn target(&self) -> usize {
+        let Some(seasons) = &self.config.seasons else {
+            return self.config.count;
+        };
+
+        let phase = (self.step % seasons.length as u64) as f32 / seasons.length as f32;
+        let scarcity = seasons.amplitude * (1.0 - (TAU * phase).cos()) / 2.0;
+
+        (self.config.count as f32 * (1.0 - scarcity)).round() as usize
+    }
+
+    /// Records that given food has been eaten, returning its nutrition.
+    pub(crate) fn eat(&mut self, food: &Food) -> f32 {
+        self.meals.resize(self.config.kinds.len(), 0);
+        self.meals[food.kind] += 1;
+        self.config.kinds[food.kind].nutrition
+    }
+
+    /// Returns how many pieces of each kind have been eaten since the last
+    /// call, i.e. during the generation that's just finished.
+    pub(crate) fn take_meals(&mut self) -> Vec<usize> {
+        let kinds = self.config.kinds.len();
+
+        std::mem::replace(&mut self.meals, vec![0; kinds])
+    }
+
+    /// Creates the initial food.
+    pub(crate) fn populate(&mut self, rng: &mut dyn RngCore, arena: &Arena) -> Vec<Food> {
+        (0..self.target())
+            .filter_map(|_| self.spawn(rng, arena))
+            .collect()
+    }
+
+    /// Replaces food that's just been eaten, unless there's already enough
+    /// food around (`present` pieces, counting the eaten one).
+    pub(crate) fn respawn(
+        &mut self,
+        rng: &mut dyn RngCore,
+        arena: &Arena,
+        present: usize,
+    ) -> Option<Food> {
+        if present > self.target() {
+            return None;
+        }
+
+        self.spawn(rng, arena)
+    }
+
+    /// Moves, ages, regrows and replenishes food; called once per step.
+    pub(crate) fn update(&mut self, rng: &mut dyn RngCore, arena: &Arena, foods: &mut Vec<Food>) {
+        self.step += 1;
+
+        if let FoodSpawn::Patches {
+            capacity, regrowth, ..
+        } = self.config.spawn
+        {
+            for site in &mut self.sites {
+                site.stock = (site.stock + regrowth).min(capacity);
+            }
+        }
+
+        if self.config.speed > 0.0 {
+            for food in foods.iter_mut() {
+                food.position += food.rotation * na::Vector2::new(0.0, self.config.speed);
+                arena.confine(&mut food.position, &mut food.rotation);
+            }
+        }
+
+        for food in foods.iter_mut() {
+            food.age += 1;
+        }
+
+        if let Some(lifetime) = self.config.lifetime {
+            foods.retain(|food| food.age < lifetime);
+        }
+
+        while foods.len() < self.target() {
+            match self.spawn(rng, arena) {
+                Some(food) => foods.push(food),
+                None => break,
+            }
+        }
+    }
+
+    /// Scatters food anew for the next generation.
+    pub(crate) fn reset(&self, rng: &mut dyn RngCore, arena: &Arena, foods: &mut [Food]) {
+        for food in foods {
+            // Not `spawn()`, since moving food that's already there around
+            // shouldn't deplete patches
+            food.position = self.position(rng, arena, None);
+            food.age = 0;
+        }
+    }
+
+    fn spawn(&mut self, rng: &mut dyn RngCore, arena: &Arena) -> Option<Food> {
+        let site = if let FoodSpawn::Patches { .. } = self.config.spawn {
+            let available: Vec<_> = (0..self.sites.len())
+                .filter(|&idx| self.sites[idx].stock >= 1.0)
+                .collect();
+
+            let site = *available.get(rng.gen_range(0..available.len().max(1)))?;
+
+            self.sites[site].stock -= 1.0;
+
+            Some(site)
+        } else {
+            None
+        };
+
+        let mut food = Food::new(self.position(rng, arena, site));
+
+        if self.config.kinds.len() > 1 {
+            food.kind = self.kind(rng);
+        }
+
+        if self.config.speed > 0.0 {
+            food.rotation = rng.gen();
+        }
+
+        Some(food)
+    }
+
+    /// Picks kind for a piece of food, according to the kinds' ratios.
+    fn kind(&self, rng: &mut dyn RngCore) -> usize {
+        let total: f32 = self.config.kinds.iter().map(|kind| kind.ratio).sum();
+        let mut roll = rng.gen_range(0.0..total);
+
+        for (idx, kind) in self.config.kinds.iter().enumerate() {
+            if roll < kind.ratio {
+                return idx;
+            }
+
+            roll -= kind.ratio;
+        }
+
+        self.config.kinds.len() - 1
+    }
+
+    /// Picks position for a piece of food, inside given site (or a random
+    /// one, if none is given).
+    fn position(
+        &self,
+        rng: &mut dyn RngCore,
+        arena: &Arena,
+        site: Option<usize>,
+    ) -> na::Point2<f32> {
+        let site = |rng: &mut dyn RngCore| {
+            site.map(|site| &self.sites[site])
+                .unwrap_or_else(|| &self.sites[rng.gen_range(0..self.sites.len())])
+                .position
+        };
+
+        match self.config.spawn {
+            FoodSpawn::Uniform => arena.spawn(rng),
+
+            FoodSpawn::Clusters { spread, .. } => arena.settle(rng, |rng| {
+                site(rng)
+                    + na::Vector2::new(rng.sample(StandardNormal), rng.sample(StandardNormal))
+                        * spread
+            }),
+
+            FoodSpawn::Patches { radius, .. } => arena.settle(rng, |rng| {
+                let distance = radius * rng.gen::<f32>().sqrt();
+                let angle = rng.gen_range(0.0..TAU);
+
+                site(rng) + na::Vector2::new(angle.cos(), angle.sin()) * distance
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn supply(rng: &mut ChaCha8Rng, config: FoodConfig) -> (FoodSupply, Vec<Food>) {
+        let mut supply = FoodSupply::new(rng, &Arena::default(), config);
+        let foods = supply.populate(rng, &Arena::default());
+
+        (supply, foods)
+    }
+
+    #[test]
+    fn clusters() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let (supply, foods) = supply(
+            &mut rng,
+            FoodConfig {
+                count: 100,
+                spawn: FoodSpawn::Clusters {
+                    clusters: 2,
+                    spread: 0.01,
+                },
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(foods.len(), 100);
+
+        for food in &foods {
+            assert!(supply
+                .sites
+                .iter()
+                .any(|site| na::distance(&site.position, &food.position) < 0.05));
+        }
+    }
+
+    #[test]
+    fn patches_deplete_and_regrow() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let (mut supply, mut foods) = supply(
+            &mut rng,
+            FoodConfig {
+                count: 10,
+                spawn: FoodSpawn::Patches {
+                    patches: 2,
+                    radius: 0.05,
+                    capacity: 3.0,
+                    regrowth: 0.5,
+                },
+                ..Default::default()
+            },
+        );
+
+        // There's only enough food in the patches for six pieces
+        assert_eq!(foods.len(), 6);
+        assert!(supply.respawn(&mut rng, &Arena::default(), 6).is_none());
+
+        // ... and it takes them two steps to regrow enough for two more
+        supply.update(&mut rng, &Arena::default(), &mut foods);
+        assert_eq!(foods.len(), 6);
+
+        supply.update(&mut rng, &Arena::default(), &mut foods);
+        assert_eq!(foods.len(), 8);
+    }
+
+    #[test]
+    fn seasons() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let (mut supply, mut foods) = supply(
+            &mut rng,
+            FoodConfig {
+                count: 20,
+                seasons: Some(Seasons {
+                    length: 100,
+                    amplitude: 0.5,
+                }),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(supply.target(), 20);
+
+        for _ in 0..50 {
+            supply.update(&mut rng, &Arena::default(), &mut foods);
+        }
+
+        // Middle of the winter: eaten food doesn't come back
+        assert_eq!(supply.target(), 10);
+        assert!(supply.respawn(&mut rng, &Arena::default(), 20).is_none());
+        assert!(supply.respawn(&mut rng, &Arena::default(), 10).is_some());
+    }
+
+    #[test]
+    fn mixes_kinds() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let (mut supply, foods) = supply(
+            &mut rng,
+            FoodConfig {
+                count: 1000,
+                kinds: vec![
+                    FoodKind {
+                        nutrition: 1.0,
+                        ratio: 3.0,
+                    },
+                    FoodKind {
+                        nutrition: -2.0,
+                        ratio: 1.0,
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+
+        let poisonous = foods.iter().filter(|food| food.kind == 1).count();
+        assert!((200..300).contains(&poisonous));
+
+        let nutrition: f32 = foods[..10].iter().map(|food| supply.eat(food)).sum();
+        let poisonous = foods[..10].iter().filter(|food| food.kind == 1).count();
+
+        approx::assert_relative_eq!(nutrition, 10.0 - 3.0 * poisonous as f32);
+        assert_eq!(supply.take_meals(), [10 - poisonous, poisonous]);
+        assert_eq!(supply.take_meals(), [0, 0]);
+    }
+
+    #[test]
+    fn rots_and_moves() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let (mut supply, mut foods) = supply(
+            &mut rng,
+            FoodConfig {
+                count: 5,
+                speed: 0.01,
+                lifetime: Some(3),
+                ..Default::default()
+            },
+        );
+
+        let positions: Vec<_> = foods.iter().map(|food| food.position).collect();
+
+        supply.update(&mut rng, &Arena::default(), &mut foods);
+
+        for (food, position) in foods.iter().zip(positions) {
+            assert_ne!(food.position, position);
+        }
+
+        supply.update(&mut rng, &Arena::default(), &mut foods);
+        supply.update(&mut rng, &Arena::default(), &mut foods);
+
+        // All the original food has rotted and got replaced
+        assert_eq!(foods.len(), 5);
+        assert!(foods.iter().all(|food| food.age == 0));
+    }
+}
@@ -0,0 +1,323 @@
+use crate::*;
+use std::io;
+
+/// Version of the genome format; bumped whenever a change to any of the
+/// serialized structs makes older genomes unreadable.
+pub const GENOME_VERSION: u32 = 1;
+
+/// Everything needed to bring a bird back to life elsewhere - e.g. in
+/// another simulation or another browser session: its eye and its brain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Genome {
+    version: u32,
+    eye: Eye,
+
+    /// Number of neurons in each layer of the brain
+    topology: Vec<usize>,
+
+    chromosome: ga::Chromosome,
+}
+
+impl Genome {
+    pub fn eye(&self) -> &Eye {
+        &self.eye
+    }
+
+    pub fn topology(&self) -> &[usize] {
+        &self.topology
+    }
+
+    pub fn chromosome(&self) -> &ga::Chromosome {
+        &self.chromosome
+    }
+
+    pub fn save(&self, writer: impl io::Write) -> Result<(), CheckpointError> {
+        serde_json::to_writer(writer, self)?;
+
+        Ok(())
+    }
+
+    pub fn load(reader: impl io::Read) -> Result<Self, CheckpointError> {
+        let genome: Self = checkpoint::read_versioned(reader, GENOME_VERSION)?;
+
+        genome.validate()?;
+
+        Ok(genome)
+    }
+
+    fn hidden(&self) -> &[usize] {
+        &self.topology[1..self.topology.len() - 1]
+    }
+
+    fn validate(&self) -> Result<(), CheckpointError> {
+        let eye = &self.eye;
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+
+        if !positive(eye.fov_range())
+            || !positive(eye.fov_angle())
+            || eye.cells() == 0
+            || eye.channels() == 0
+        {
+            return Err(CheckpointError::InvalidGenome("eye is malformed"));
+        }
+
+        if self.topology.len() < 2 || self.topology.contains(&0) {
+            return Err(CheckpointError::InvalidGenome("topology is malformed"));
+        }
+
+        let topology: Vec<_> = Brain::topology(&self.eye, self.hidden())
+            .iter()
+            .map(|layer| layer.neurons)
+            .collect();
+
+        if self.topology != topology {
+            return Err(CheckpointError::InvalidGenome(
+                "topology doesn't match the eye",
+            ));
+        }
+
+        let weights: usize = topology
+            .windows(2)
+            .map(|layers| (layers[0] + 1) * layers[1])
+            .sum();
+
+        if self.chromosome.len() != weights {
+            return Err(CheckpointError::InvalidGenome(
+                "chromosome doesn't match the topology",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Animal {
+    pub fn genome(&self) -> Genome {
+        Genome {
+            version: GENOME_VERSION,
+            eye: self.eye.clone(),
+            topology: self
+                .brain
+                .nn
+                .topology()
+                .iter()
+                .map(|layer| layer.neurons)
+                .collect(),
+            chromosome: self.as_chromosome(),
+        }
+    }
+
+    pub fn from_genome(genome: Genome, rng: &mut dyn RngCore) -> Result<Self, CheckpointError> {
+        genome.validate()?;
+
+        let hidden = genome.hidden().to_vec();
+
+        Ok(Self::from_chromosome(
+            genome.chromosome,
+            rng,
+            genome.eye,
+            &hidden,
+        ))
+    }
+}
+
+impl Simulation {
+    /// Creates a random simulation with given genomes already living in it;
+    /// eyes and brains of the random birds follow the first genome.
+    pub fn from_genomes(
+        rng: &mut dyn RngCore,
+        genomes: Vec<Genome>,
+        animals: i32,
+        foods: i32,
+    ) -> Result<Self, CheckpointError> {
+        let genome = genomes
+            .first()
+            .ok_or(CheckpointError::InvalidGenome("no genomes given"))?;
+
+        genome.validate()?;
+
+        let world = World::populate(
+            rng,
+            Arena::default(),
+            FoodConfig {
+                count: foods as usize,
+                ..Default::default()
+            },
+            animals,
+            &genome.eye,
+            genome.hidden(),
+        );
+
+        let mut simulation = Self::new(world);
+
+        simulation.inject(rng, genomes)?;
+
+        Ok(simulation)
+    }
+
+    /// Animal that has eaten the most in the current generation
+    pub fn best_animal(&self) -> Option<&Animal> {
+        self.world
+            .animals
+            .iter()
+            .max_by(|a, b| a.satiation.total_cmp(&b.satiation))
+    }
+
+    /// Puts given genomes into the current population, in place of the
+    /// animals that have eaten the least so far; the genomes have to have
+    /// brains of the same shape as the population, so that they can be
+    /// crossed over with it.
+    pub fn inject(
+        &mut self,
+        rng: &mut dyn RngCore,
+        genomes: Vec<Genome>,
+    ) -> Result<(), CheckpointError> {
+        if genomes.len() > self.world.animals.len() {
+            return Err(CheckpointError::InvalidGenome("more genomes than animals"));
+        }
+
+        for genome in &genomes {
+            genome.validate()?;
+        }
+
+        if let Some(animal) = self.world.animals.first() {
+            let topology = animal.genome().topology;
+
+            if genomes.iter().any(|genome| genome.topology != topology) {
+                return Err(CheckpointError::InvalidGenome(
+                    "brain doesn't match the population",
+                ));
+            }
+        }
+
+        let mut worst: Vec<_> = (0..self.world.animals.len()).collect();
+        worst.sort_by(|&a, &b| {
+            let animals = &self.world.animals;
+            animals[a].satiation.total_cmp(&animals[b].satiation)
+        });
+
+        for (idx, genome) in worst.into_iter().zip(genomes) {
+            let mut animal = Animal::from_genome(genome, rng)?;
+            animal.position = self.world.arena.place(rng, animal.position);
+
+            self.world
+                .register(&mut animal, &[], self.generation as u32);
+
+            self.world.animals[idx] = animal;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn simulation(rng: &mut ChaCha8Rng, cells: usize) -> Simulation {
+        let mut sim = Simulation::random(rng, 10, 20, 0.25, 3.9, cells);
+
+        for _ in 0..100 {
+            sim.step(rng, &Default::default());
+        }
+
+        sim
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let sim = simulation(&mut rng, 9);
+        let best = sim.best_animal().unwrap();
+
+        let mut genome = Vec::new();
+        best.genome().save(&mut genome).unwrap();
+
+        let genome = Genome::load(genome.as_slice()).unwrap();
+        assert_eq!(genome.topology(), &[9, 18, 2]);
+
+        let animal = Animal::from_genome(genome, &mut rng).unwrap();
+        assert_eq!(animal.as_chromosome(), best.as_chromosome());
+        assert_eq!(animal.eye().cells(), 9);
+    }
+
+    #[test]
+    fn rejects_mismatched_chromosome() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let sim = simulation(&mut rng, 9);
+
+        let mut genome = sim.best_animal().unwrap().genome();
+        genome.chromosome = genome.chromosome.into_iter().skip(1).collect();
+
+        let mut bytes = Vec::new();
+        genome.save(&mut bytes).unwrap();
+
+        assert!(matches!(
+            Genome::load(bytes.as_slice()),
+            Err(CheckpointError::InvalidGenome(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_topology() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let sim = simulation(&mut rng, 9);
+        let genome = serde_json::to_value(sim.best_animal().unwrap().genome()).unwrap();
+
+        for (field, value) in [
+            ("/topology", serde_json::json!([])),
+            ("/topology", serde_json::json!([9])),
+            ("/topology", serde_json::json!([9, 0, 2])),
+            ("/eye/cells", serde_json::json!(0)),
+            ("/eye/channels", serde_json::json!(0)),
+            ("/eye/fov_range", serde_json::json!(-1.0)),
+        ] {
+            let mut genome = genome.clone();
+            *genome.pointer_mut(field).unwrap() = value;
+
+            assert!(matches!(
+                Genome::load(genome.to_string().as_bytes()),
+                Err(CheckpointError::InvalidGenome(_))
+            ));
+
+            // Genomes deserialized some other way get checked, too
+            let genome: Genome = serde_json::from_value(genome).unwrap();
+
+            assert!(matches!(
+                Animal::from_genome(genome, &mut rng),
+                Err(CheckpointError::InvalidGenome(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn injects_in_place_of_worst() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut sim = simulation(&mut rng, 9);
+        let genome = sim.best_animal().unwrap().genome();
+
+        let worst = (0..sim.world().animals().len())
+            .min_by(|&a, &b| {
+                let animals = sim.world().animals();
+                animals[a].satiation().total_cmp(&animals[b].satiation())
+            })
+            .unwrap();
+
+        sim.inject(&mut rng, vec![genome.clone()]).unwrap();
+
+        assert_eq!(
+            sim.world().animals()[worst].as_chromosome(),
+            *genome.chromosome()
+        );
+
+        // Brains of a different shape couldn't be crossed over with the
+        // rest of the population
+        let other = simulation(&mut rng, 5).best_animal().unwrap().genome();
+
+        assert!(matches!(
+            sim.inject(&mut rng, vec![other]),
+            Err(CheckpointError::InvalidGenome(_))
+        ));
+    }
+}
@@ -1,23 +1,56 @@
 mod animal;
 mod animal_individual;
+mod arena;
 mod brain;
+mod checkpoint;
+mod collision;
 mod eye;
 mod food;
+mod food_supply;
+mod genome;
+mod lineage;
+mod novelty;
+mod optimizer;
+mod replay;
+mod reproduction;
+mod scenario;
+mod statistics;
+mod step_params;
 mod world;
 
-use self::animal_individual::*;
-pub use self::{animal::*, brain::*, eye::*, food::*, world::*};
+pub use self::{
+    animal::*, animal_individual::*, arena::*, brain::*, checkpoint::*, collision::*, eye::*,
+    food::*, food_supply::*, genome::*, lineage::*, novelty::*, optimizer::*, replay::*,
+    reproduction::*, scenario::*, statistics::*, step_params::*, world::*,
+};
 use lib_genetic_algorithm as ga;
 use lib_neural_network as nn;
 use nalgebra as na;
 use rand::{Rng, RngCore};
-use std::vec;
+pub use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, vec};
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Simulation {
     world: World,
-    ga: ga::GeneticAlgorithm<ga::RouletteWheelSelection>,
+    optimizer: Optimizer,
+    novelty: Option<NoveltySearch>,
+
+    #[serde(default)]
+    reproduction: Option<Reproduction>,
+
+    #[serde(skip)]
+    observers: Observers,
+
+    #[serde(default)]
+    lineage: Option<Lineage>,
+
     age: i32,
     pub generation: i32,
+    history: Vec<Statistics>,
 }
 
 impl Simulation {
@@ -29,94 +62,357 @@ impl Simulation {
         fov_angle: f32,
         cells: usize,
     ) -> Self {
-        let world = World::random(rng, animals, foods, fov_range, fov_angle, cells);
-
-        let ga = ga::GeneticAlgorithm::new(
-            ga::RouletteWheelSelection,
-            ga::UniformCrossover,
-            ga::GaussianMutation::new(0.01, 0.3),
-        );
+        Self::new(World::random(
+            rng, animals, foods, fov_range, fov_angle, cells,
+        ))
+    }
 
+    pub fn new(world: World) -> Self {
         Self {
             world,
-            ga,
+            optimizer: Optimizer::default(),
+            novelty: None,
+            reproduction: None,
+            observers: Observers::default(),
+            lineage: None,
             age: 0,
             generation: 0,
+            history: Vec::new(),
         }
     }
 
+    /// Replaces the default genetic algorithm with another optimizer,
+    /// e.g. differential evolution or CMA-ES.
+    pub fn with_optimizer(mut self, optimizer: Optimizer) -> Self {
+        self.optimizer = optimizer;
+        self
+    }
+
+    pub fn optimizer(&self) -> &Optimizer {
+        &self.optimizer
+    }
+
+    /// Switches evolution from rewarding satiation to rewarding novel
+    /// behaviour.
+    pub fn with_novelty(mut self, novelty: NoveltySearch) -> Self {
+        self.novelty = Some(novelty);
+        self
+    }
+
+    pub fn novelty(&self) -> Option<&NoveltySearch> {
+        self.novelty.as_ref()
+    }
+
+    /// Switches from generational evolution to the steady-state one, in
+    /// which birds reproduce and die on their own (novelty search is then
+    /// not used, and the optimizer only tells how children get bred - see
+    /// `Optimizer::breeder()`).
+    ///
+    /// Immigrants, restarts and speciation are not supported in this mode;
+    /// the genetic algorithm panics when it's configured to use them.
+    pub fn with_reproduction(mut self, reproduction: Reproduction) -> Self {
+        assert!(reproduction.min_animals > 0);
+        assert!(reproduction.min_animals <= reproduction.max_animals);
+
+        reproduction.reset_energy(&mut self.world);
+        self.reproduction = Some(reproduction);
+        self
+    }
+
+    pub fn reproduction(&self) -> Option<&Reproduction> {
+        self.reproduction.as_ref()
+    }
+
+    /// Adds an observer that gets notified about everything that happens
+    /// while the birds are evolved; pass an `Arc` to keep access to it.
+    ///
+    /// Observers are not saved in checkpoints.
+    pub fn with_observer(mut self, observer: impl ga::Observer + 'static) -> Self {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+
+    /// Starts keeping track of who descends from whom.
+    ///
+    /// Only the genetic algorithm and reproduction tell who's bred whom -
+    /// differential evolution and CMA-ES build each child out of the whole
+    /// population, so their children are recorded without parents.
+    pub fn with_lineage(mut self) -> Self {
+        let mut lineage = Lineage::default();
+
+        lineage.record(&self.world.animals);
+        self.lineage = Some(lineage);
+        self
+    }
+
+    pub fn lineage(&self) -> Option<&Lineage> {
+        self.lineage.as_ref()
+    }
+
     pub fn world(&self) -> &World {
         &self.world
     }
 
-    pub fn step(
-        &mut self,
-        rng: &mut dyn RngCore,
-        speed_min: f32,
-        speed_max: f32,
-        speed_accel: f32,
-        rotation_accel: f32,
-        generation_length: i32,
-        fov_range: f32,
-        fov_angle: f32,
-        cells: usize,
-    ) {
+    /// Statistics of all the generations that have finished so far
+    pub fn history(&self) -> &[Statistics] {
+        &self.history
+    }
+
+    /// Performs a single step; returns the population's statistics when
+    /// this step has finished a generation.
+    pub fn step(&mut self, rng: &mut dyn RngCore, params: &StepParams) -> Option<Statistics> {
         self.process_collisions(rng);
-        self.process_brains(speed_min, speed_max, speed_accel, rotation_accel);
+        self.process_brains(
+            params.speed_min,
+            params.speed_max,
+            params.speed_accel,
+            params.rotation_accel,
+        );
         self.process_movements();
+        self.process_food(rng);
+
+        if let Some(reproduction) = &self.reproduction {
+            let breeder = self
+                .optimizer
+                .breeder(self.generation as usize, &self.observers);
+
+            reproduction.process(rng, &mut self.world, self.generation as u32, &breeder);
+
+            if let Some(lineage) = &mut self.lineage {
+                lineage.record(&self.world.animals);
+            }
+        }
+
+        // Novelty search isn't used in the steady-state mode, where birds
+        // come and go all the time
+        if let (Some(novelty), None) = (&mut self.novelty, &self.reproduction) {
+            novelty.observe(&self.world.arena, &self.world.animals);
+        }
 
         self.age += 1;
 
-        if self.age > generation_length {
+        if self.age > params.generation_length {
+            let meals = self.world.food.take_meals();
+            let (diversity, behavioural_diversity) = self.diversity();
+
+            let ga = if self.reproduction.is_some() {
+                self.summarize()
+            } else {
+                self.evolve(rng, params.fov_range, params.fov_angle, params.cells)
+            };
+
+            let stats = Statistics {
+                generation: self.generation,
+                ga,
+                meals,
+                diversity,
+                behavioural_diversity,
+            };
+
             self.generation += 1;
-            self.evolve(rng, fov_range, fov_angle, cells);
+            self.history.push(stats.clone());
+
+            Some(stats)
+        } else {
+            None
         }
     }
 
-    fn evolve(&mut self, rng: &mut dyn RngCore, fov_range: f32, fov_angle: f32, cells: usize) {
+    /// Keeps stepping until given controller decides that evolution is
+    /// done; returns why it's stopped.
+    ///
+    /// `on_generation` gets called with statistics of each generation, as
+    /// soon as it finishes.
+    pub fn run(
+        &mut self,
+        rng: &mut dyn RngCore,
+        params: &StepParams,
+        controller: &mut ga::RunController,
+        mut on_generation: impl FnMut(&Statistics),
+    ) -> ga::StopReason {
+        loop {
+            let Some(stats) = self.step(rng, params) else {
+                continue;
+            };
+
+            on_generation(&stats);
+
+            let population: Vec<_> = self
+                .world
+                .animals
+                .iter()
+                .map(AnimalIndividual::from_animal)
+                .collect();
+
+            if let Some(reason) = controller.update(&stats.ga, &population) {
+                return reason;
+            }
+        }
+    }
+
+    fn evolve(
+        &mut self,
+        rng: &mut dyn RngCore,
+        fov_range: f32,
+        fov_angle: f32,
+        cells: usize,
+    ) -> ga::Statistics {
         self.age = 0;
 
         // Step 1: Prepare birdies to be sent into the genetic algorithm
-        let current_population: Vec<_> = self
+        let mut current_population: Vec<_> = self
             .world
             .animals
             .iter()
             .map(AnimalIndividual::from_animal)
             .collect();
 
+        if let Some(novelty) = &mut self.novelty {
+            let scores = novelty.evaluate(&self.world.animals);
+
+            for (individual, score) in current_population.iter_mut().zip(scores) {
+                individual.fitness = score;
+            }
+        }
+
+        if let Some(lineage) = &mut self.lineage {
+            lineage.score(&self.world.animals);
+        }
+
+        // (children inherit shape of their parents' brains and the number
+        // of food kinds their eyes can tell apart)
+        let (hidden, channels) = self
+            .world
+            .animals
+            .first()
+            .map(|animal| (animal.brain.hidden(), animal.eye.channels()))
+            .unwrap_or((Vec::new(), 1));
+
+        let eye = Eye::default(fov_range, fov_angle, cells).with_channels(channels);
+
+        let brains = {
+            let (eye, hidden) = (eye.clone(), hidden.clone());
+
+            move |rng: &mut dyn RngCore| Brain::random(rng, &eye, &hidden).as_chromosome()
+        };
+
         // Step 2: Evolve birdies
-        let evolved_population = self.ga.evolve(rng, &current_population);
+        let parentage = Arc::new(ga::Parentage::default());
+        let mut observers = self.observers.clone();
+
+        observers.push(parentage.clone());
 
-        self.world.animals = evolved_population
+        let (evolved_population, stats) = self.optimizer.evolve_observed(
+            rng,
+            &current_population,
+            self.generation as usize,
+            &observers,
+            brains,
+        );
+
+        let ids: Vec<_> = self.world.animals.iter().map(|animal| animal.id).collect();
+        let generation = self.generation as u32 + 1;
+
+        let animals = evolved_population
             .into_iter()
-            .map(|individual| individual.into_animal(rng, fov_range, fov_angle, cells))
+            .enumerate()
+            .map(|(idx, individual)| {
+                let mut animal = individual.into_animal(rng, eye.clone(), &hidden);
+                animal.position = self.world.arena.place(rng, animal.position);
+
+                let parents: Vec<_> = parentage
+                    .parents(idx)
+                    .map(|parents| parents.iter().map(|&parent| ids[parent]).collect())
+                    .unwrap_or_default();
+
+                self.world.register(&mut animal, &parents, generation);
+                animal
+            })
             .collect();
 
-        for food in &mut self.world.foods {
-            food.position = rng.gen();
+        self.world.animals = animals;
+
+        if let Some(lineage) = &mut self.lineage {
+            lineage.record(&self.world.animals);
         }
+
+        self.world
+            .food
+            .reset(rng, &self.world.arena, &mut self.world.foods);
+
+        stats
     }
 
-    fn process_collisions(&mut self, rng: &mut dyn RngCore) {
-        for animal in &mut self.world.animals {
-            for food in &mut self.world.foods {
-                let distance = na::distance(&animal.position, &food.position);
+    /// Measures how diverse the current generation is, both in genes and in
+    /// behaviour.
+    fn diversity(&self) -> (ga::Diversity, f32) {
+        let population: Vec<_> = self
+            .world
+            .animals
+            .iter()
+            .map(AnimalIndividual::from_animal)
+            .collect();
 
-                if distance <= 0.01 {
-                    animal.satiation += 1;
-                    food.position = rng.gen();
-                }
-            }
+        let behaviours = match (&self.novelty, &self.reproduction) {
+            (Some(novelty), None) => novelty.describe(&self.world.animals),
+
+            _ => self
+                .world
+                .animals
+                .iter()
+                .map(|animal| vec![animal.position.x, animal.position.y])
+                .collect(),
+        };
+
+        (
+            ga::Diversity::new(&population),
+            behavioural_diversity(&behaviours),
+        )
+    }
+
+    /// Ends a generation in the steady-state mode, where there's nothing to
+    /// evolve - we just take note of how much everybody has eaten.
+    fn summarize(&mut self) -> ga::Statistics {
+        self.age = 0;
+
+        let population: Vec<_> = self
+            .world
+            .animals
+            .iter()
+            .map(AnimalIndividual::from_animal)
+            .collect();
+
+        if let Some(lineage) = &mut self.lineage {
+            lineage.score(&self.world.animals);
+        }
+
+        for animal in &mut self.world.animals {
+            animal.satiation = 0.0;
         }
+
+        ga::Statistics::new(&population, 1)
+    }
+
+    fn process_collisions(&mut self, rng: &mut dyn RngCore) {
+        self.world.feed_animals(rng);
+    }
+
+    fn process_food(&mut self, rng: &mut dyn RngCore) {
+        self.world
+            .food
+            .update(rng, &self.world.arena, &mut self.world.foods);
     }
 
     fn process_movements(&mut self) {
         for animal in &mut self.world.animals {
             animal.position += animal.rotation * na::Vector2::new(0.0, animal.speed);
 
-            animal.position.x = na::wrap(animal.position.x, 0.0, 1.0);
-            animal.position.y = na::wrap(animal.position.y, 0.0, 1.0);
+            self.world
+                .arena
+                .confine(&mut animal.position, &mut animal.rotation);
         }
+
+        self.world.separate_animals();
     }
 
     fn process_brains(
@@ -126,11 +422,10 @@ impl Simulation {
         speed_accel: f32,
         rotation_accel: f32,
     ) {
-        for animal in &mut self.world.animals {
-            let vision =
-                animal
-                    .eye
-                    .process_vision(animal.position, animal.rotation, &self.world.foods);
+        let foods = &self.world.foods;
+
+        let think = |animal: &mut Animal| {
+            let vision = animal.vision(foods);
 
             let response = animal.brain.nn.propagate(vision);
             // ---
@@ -158,6 +453,14 @@ impl Simulation {
             // because rotation automatically wraps from 2*PI back to 0 -
             // we've already witnessed that when we were testing eyes,
             // inside `fn rotations { ... }`.)
-        }
+        };
+
+        // Each bird thinks on its own, so with the `parallel` feature they
+        // can all do it at once
+        #[cfg(feature = "parallel")]
+        self.world.animals.par_iter_mut().for_each(think);
+
+        #[cfg(not(feature = "parallel"))]
+        self.world.animals.iter_mut().for_each(think);
     }
 }
@@ -0,0 +1,238 @@
+use crate::*;
+use std::collections::{BTreeSet, VecDeque};
+
+/// Family tree of all the birds that have lived since lineage tracking got
+/// enabled (see `Simulation::with_lineage()`).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Lineage {
+    /// All the birds, sorted by their identifiers
+    birds: Vec<Ancestor>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ancestor {
+    pub id: u64,
+
+    /// Identifiers of the bird's parents - they are not necessarily part of
+    /// the lineage, e.g. if they had lived before the tracking got enabled
+    pub parents: Vec<u64>,
+
+    /// Generation the bird has been born in
+    pub generation: u32,
+
+    /// Satiation the bird has reached by the end of the last generation
+    /// it's lived through
+    pub fitness: Option<f32>,
+}
+
+impl Lineage {
+    pub fn len(&self) -> usize {
+        self.birds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.birds.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Ancestor> {
+        self.birds.iter()
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Ancestor> {
+        self.position(id).map(|idx| &self.birds[idx])
+    }
+
+    /// Identifiers of given bird's children.
+    pub fn children(&self, id: u64) -> Vec<u64> {
+        self.birds
+            .iter()
+            .filter(|bird| bird.parents.contains(&id))
+            .map(|bird| bird.id)
+            .collect()
+    }
+
+    /// Identifiers of given bird's parents, grandparents and so on (as far
+    /// as the lineage goes back).
+    pub fn ancestors(&self, id: u64) -> BTreeSet<u64> {
+        self.traverse(id, |bird| bird.parents.clone())
+    }
+
+    /// Identifiers of given bird's children, grandchildren and so on.
+    pub fn descendants(&self, id: u64) -> BTreeSet<u64> {
+        self.traverse(id, |bird| self.children(bird.id))
+    }
+
+    /// Exports the lineage as a GraphViz graph, with edges going from
+    /// parents to their children.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph lineage {\n");
+
+        for bird in &self.birds {
+            let fitness = bird
+                .fitness
+                .map(|fitness| format!("\\nfitness {:.1}", fitness))
+                .unwrap_or_default();
+
+            dot.push_str(&format!(
+                "    {} [label=\"#{} (gen {}){}\"];\n",
+                bird.id, bird.id, bird.generation, fitness
+            ));
+
+            for parent in &bird.parents {
+                if self.position(*parent).is_some() {
+                    dot.push_str(&format!("    {} -> {};\n", parent, bird.id));
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Exports the lineage as a JSON array of birds.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.birds).expect("lineage is always serializable")
+    }
+
+    /// Notes down birds that have been born since the last call.
+    pub(crate) fn record(&mut self, animals: &[Animal]) {
+        let last = self.birds.last().map(|bird| bird.id);
+
+        let mut newborns: Vec<_> = animals
+            .iter()
+            .filter(|animal| last.map_or(true, |last| animal.id > last))
+            .map(|animal| Ancestor {
+                id: animal.id,
+                parents: animal.parents.clone(),
+                generation: animal.generation,
+                fitness: None,
+            })
+            .collect();
+
+        newborns.sort_by_key(|bird| bird.id);
+        self.birds.extend(newborns);
+    }
+
+    /// Notes down how well the birds have done in the generation that has
+    /// just finished.
+    pub(crate) fn score(&mut self, animals: &[Animal]) {
+        for animal in animals {
+            if let Some(idx) = self.position(animal.id) {
+                self.birds[idx].fitness = Some(animal.satiation);
+            }
+        }
+    }
+
+    fn position(&self, id: u64) -> Option<usize> {
+        self.birds.binary_search_by_key(&id, |bird| bird.id).ok()
+    }
+
+    /// Walks the lineage breadth-first, starting from (but not including)
+    /// given bird.
+    fn traverse(&self, id: u64, next: impl Fn(&Ancestor) -> Vec<u64>) -> BTreeSet<u64> {
+        let mut found = BTreeSet::new();
+        let mut pending = VecDeque::from([id]);
+
+        while let Some(id) = pending.pop_front() {
+            let Some(bird) = self.get(id) else {
+                continue;
+            };
+
+            for relative in next(bird) {
+                if found.insert(relative) {
+                    pending.push_back(relative);
+                }
+            }
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn records_generations() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let mut sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9).with_lineage();
+
+        let params = StepParams {
+            generation_length: 200,
+            ..Default::default()
+        };
+
+        for _ in 0..402 {
+            sim.step(&mut rng, &params);
+        }
+
+        let lineage = sim.lineage().unwrap();
+
+        // Founders, then two generations of their descendants
+        assert_eq!(lineage.len(), 30);
+        assert!(lineage.iter().take(20).all(|bird| bird.fitness.is_some()));
+
+        for animal in sim.world().animals() {
+            assert!(animal.id() >= 20);
+            assert_eq!(animal.generation(), 2);
+            assert!(!animal.parents().is_empty());
+
+            let ancestors = lineage.ancestors(animal.id());
+
+            assert!(ancestors.iter().any(|&id| id < 10));
+            assert!(ancestors.iter().any(|&id| (10..20).contains(&id)));
+        }
+
+        // Everybody descends from the founders
+        let descendants: BTreeSet<_> = (0..10)
+            .flat_map(|founder| lineage.descendants(founder))
+            .collect();
+
+        assert_eq!(descendants, (10..30).collect());
+
+        let dot = lineage.to_dot();
+
+        assert!(dot.starts_with("digraph lineage {"));
+        assert!(dot.contains("[label=\"#29 (gen 2)\"]"));
+
+        for child in lineage.children(0) {
+            assert!(dot.contains(&format!("0 -> {};", child)));
+        }
+
+        let json: Vec<Ancestor> = serde_json::from_str(&lineage.to_json()).unwrap();
+
+        assert_eq!(json, lineage.birds);
+    }
+
+    #[test]
+    fn records_orphans_without_genetic_algorithm() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let mut sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9)
+            .with_optimizer(Optimizer::CmaEs(ga::CmaEs::new(0.5)))
+            .with_lineage();
+
+        let params = StepParams {
+            generation_length: 100,
+            ..Default::default()
+        };
+
+        for _ in 0..101 {
+            sim.step(&mut rng, &params);
+        }
+
+        let lineage = sim.lineage().unwrap();
+
+        assert_eq!(lineage.len(), 20);
+
+        for animal in sim.world().animals() {
+            assert!(animal.id() >= 10);
+            assert_eq!(animal.generation(), 1);
+            assert!(animal.parents().is_empty());
+            assert!(lineage.ancestors(animal.id()).is_empty());
+        }
+    }
+}
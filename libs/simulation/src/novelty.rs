@@ -0,0 +1,208 @@
+use crate::*;
+
+/// What we look at when comparing how two birds behaved.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Behaviour {
+    /// Where the bird was when the generation ended
+    FinalPosition,
+
+    /// How much time the bird has spent in each cell of a
+    /// `resolution` x `resolution` grid laid over the world
+    VisitedCells { resolution: usize },
+}
+
+/// Novelty-search driver: instead of rewarding birds only for eating,
+/// rewards them for behaving differently from the rest of the population
+/// and from the behaviours archived in previous generations - which gives
+/// evolution something to follow even when hardly anybody finds food.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoveltySearch {
+    behaviour: Behaviour,
+    archive: ga::NoveltyArchive,
+
+    /// How much satiation is blended into the novelty score:
+    /// - 0.0 = pure novelty search
+    /// - 1.0 = each eaten food is worth as much as 1.0 of novelty
+    fitness_weight: f32,
+
+    visits: Vec<Vec<f32>>,
+}
+
+impl NoveltySearch {
+    pub fn new(behaviour: Behaviour, archive: ga::NoveltyArchive, fitness_weight: f32) -> Self {
+        if let Behaviour::VisitedCells { resolution } = behaviour {
+            assert!(resolution > 0);
+        }
+
+        assert!(fitness_weight >= 0.0);
+
+        Self {
+            behaviour,
+            archive,
+            fitness_weight,
+            visits: Vec::new(),
+        }
+    }
+
+    pub fn archive(&self) -> &ga::NoveltyArchive {
+        &self.archive
+    }
+
+    pub(crate) fn observe(&mut self, arena: &Arena, animals: &[Animal]) {
+        let Behaviour::VisitedCells { resolution } = self.behaviour else {
+            return;
+        };
+
+        self.visits
+            .resize_with(animals.len(), || vec![0.0; resolution * resolution]);
+
+        // (the grid spans the whole arena, whatever its size)
+        let cell = |coord: f32, size: f32| {
+            ((coord / size * resolution as f32) as usize).min(resolution - 1)
+        };
+
+        for (animal, visits) in animals.iter().zip(&mut self.visits) {
+            let x = cell(animal.position.x, arena.width);
+            let y = cell(animal.position.y, arena.height);
+
+            visits[y * resolution + x] += 1.0;
+        }
+    }
+
+    /// Scores each animal by novelty of its behaviour (blended with its
+    /// satiation) and starts observing a fresh generation.
+    pub(crate) fn evaluate(&mut self, animals: &[Animal]) -> Vec<f32> {
+        let behaviours = self.describe(animals);
+        let novelty = self.archive.evaluate(&behaviours);
+
+        self.visits.clear();
+
+        animals
+            .iter()
+            .zip(novelty)
+            .map(|(animal, novelty)| novelty + self.fitness_weight * animal.satiation)
+            .collect()
+    }
+
+    pub(crate) fn describe(&self, animals: &[Animal]) -> Vec<Vec<f32>> {
+        match self.behaviour {
+            Behaviour::FinalPosition => animals
+                .iter()
+                .map(|animal| vec![animal.position.x, animal.position.y])
+                .collect(),
+
+            Behaviour::VisitedCells { resolution } => (0..animals.len())
+                .map(|idx| match self.visits.get(idx) {
+                    Some(visits) => {
+                        let total: f32 = visits.iter().sum::<f32>().max(1.0);
+
+                        visits.iter().map(|visits| visits / total).collect()
+                    }
+                    None => vec![0.0; resolution * resolution],
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Average (Euclidean) distance between two behaviours.
+pub(crate) fn behavioural_diversity(behaviours: &[Vec<f32>]) -> f32 {
+    if behaviours.len() < 2 {
+        return 0.0;
+    }
+
+    let mut sum = 0.0;
+
+    for (idx, a) in behaviours.iter().enumerate() {
+        for b in &behaviours[idx + 1..] {
+            sum += a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f32>()
+                .sqrt();
+        }
+    }
+
+    let pairs = behaviours.len() * (behaviours.len() - 1) / 2;
+
+    sum / pairs as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn scores_visited_cells() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = World::random(&mut rng, 3, 0, 0.25, 3.9, 3);
+
+        let mut novelty = NoveltySearch::new(
+            Behaviour::VisitedCells { resolution: 2 },
+            ga::NoveltyArchive::new(2, 100.0),
+            0.5,
+        );
+
+        let left = na::Point2::new(0.25, 0.25);
+        let right = na::Point2::new(0.75, 0.25);
+
+        for position in [left, right] {
+            world.animals[0].position = left;
+            world.animals[1].position = right;
+            world.animals[2].position = position;
+
+            novelty.observe(&world.arena, &world.animals);
+        }
+
+        world.animals[2].satiation = 1.0;
+
+        assert_eq!(
+            novelty.describe(&world.animals),
+            [
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.5, 0.5, 0.0, 0.0],
+            ]
+        );
+
+        let actual = novelty.evaluate(&world.animals);
+
+        let expected = [
+            (2.0f32.sqrt() + 0.5f32.sqrt()) / 2.0,
+            (2.0f32.sqrt() + 0.5f32.sqrt()) / 2.0,
+            0.5f32.sqrt() + 0.5,
+        ];
+
+        approx::assert_relative_eq!(actual.as_slice(), expected.as_slice());
+
+        // Evaluation starts a fresh generation
+        assert_eq!(novelty.describe(&world.animals)[0], [0.0; 4]);
+    }
+
+    #[test]
+    fn spans_whole_arena() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = World::random(&mut rng, 2, 0, 0.25, 3.9, 3);
+
+        world.arena.width = 2.0;
+        world.animals[0].position = na::Point2::new(1.25, 0.5);
+        world.animals[1].position = na::Point2::new(1.75, 0.5);
+
+        let mut novelty = NoveltySearch::new(
+            Behaviour::VisitedCells { resolution: 4 },
+            ga::NoveltyArchive::new(1, 100.0),
+            0.0,
+        );
+
+        novelty.observe(&world.arena, &world.animals);
+
+        let behaviours = novelty.describe(&world.animals);
+
+        // Birds in the right half of the arena still end up in different
+        // columns
+        assert_eq!(behaviours[0][2 * 4 + 2], 1.0);
+        assert_eq!(behaviours[1][2 * 4 + 3], 1.0);
+    }
+}
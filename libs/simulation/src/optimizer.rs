@@ -0,0 +1,356 @@
+use crate::*;
+use std::{fmt, sync::Arc};
+
+/// Optimizer used to evolve the birds.
+///
+/// Unlike a `Box<dyn ga::Optimizer>`, this can be saved in a checkpoint -
+/// together with whatever state the optimizer has accumulated so far.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Optimizer {
+    GeneticAlgorithm(GeneticAlgorithmConfig),
+    DifferentialEvolution(ga::DifferentialEvolution),
+    CmaEs(ga::CmaEs),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GeneticAlgorithmConfig {
+    pub mutation_chance: f32,
+    pub mutation_coeff: f32,
+    pub speciation: Option<ga::Speciation>,
+
+    /// Range brain weights are kept within; by default mutation can push
+    /// them arbitrarily far
+    #[serde(default)]
+    pub bounds: Option<ga::Bounds>,
+
+    /// Transformation of satiation before selection, so that a single
+    /// lucky bird doesn't end up parenting most of the next generation
+    #[serde(default)]
+    pub scaling: Option<ga::Scaling>,
+
+    /// Fraction of each generation that's made of random birds
+    #[serde(default)]
+    pub immigrants: f32,
+
+    /// When (and how much of) the population gets replaced with random
+    /// birds, once it's stopped improving
+    #[serde(default)]
+    pub restart: Option<ga::Restart>,
+
+    /// For how long the population hasn't improved; kept here for the same
+    /// reason as `generation`
+    #[serde(default)]
+    pub plateau: ga::Plateau,
+
+    /// Number of generations evolved so far; the algorithm is rebuilt each
+    /// generation, so this is what keeps scaling's schedule going
+    #[serde(default)]
+    pub generation: usize,
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Self::GeneticAlgorithm(Default::default())
+    }
+}
+
+impl Default for GeneticAlgorithmConfig {
+    fn default() -> Self {
+        Self {
+            mutation_chance: 0.01,
+            mutation_coeff: 0.3,
+            speciation: None,
+            bounds: None,
+            scaling: None,
+            immigrants: 0.0,
+            restart: None,
+            plateau: ga::Plateau::default(),
+            generation: 0,
+        }
+    }
+}
+
+impl GeneticAlgorithmConfig {
+    pub fn build(&self) -> ga::GeneticAlgorithm<ga::RouletteWheelSelection> {
+        let mut ga = ga::GeneticAlgorithm::new(
+            ga::RouletteWheelSelection,
+            ga::UniformCrossover,
+            ga::GaussianMutation::new(self.mutation_chance, self.mutation_coeff),
+        )
+        .with_generation(self.generation)
+        .with_immigrants(self.immigrants)
+        .with_plateau(self.plateau);
+
+        if let Some(bounds) = &self.bounds {
+            ga = ga.with_repair(bounds.clone());
+        }
+
+        if let Some(scaling) = self.scaling {
+            ga = ga.with_scaling(scaling);
+        }
+
+        if let Some(restart) = self.restart {
+            ga = ga.with_restart(restart);
+        }
+
+        match &self.speciation {
+            Some(speciation) => ga.with_speciation(speciation.clone()),
+            None => ga,
+        }
+    }
+}
+
+/// Observers of the evolution.
+///
+/// They can't be saved, so after a simulation gets restored from a
+/// checkpoint they have to be added again.
+#[derive(Clone, Default)]
+pub(crate) struct Observers(Vec<Arc<dyn ga::Observer>>);
+
+impl Observers {
+    pub(crate) fn push(&mut self, observer: Arc<dyn ga::Observer>) {
+        self.0.push(observer);
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
+
+impl Optimizer {
+    /// Makes sure the genetic algorithm's settings are within the ranges
+    /// its builder accepts - which a deserialized optimizer doesn't have to
+    /// be, while it gets rebuilt from them each generation.
+    pub(crate) fn validate(&self) -> Result<(), CheckpointError> {
+        let Self::GeneticAlgorithm(config) = self else {
+            return Ok(());
+        };
+
+        let check = |valid: bool, reason| {
+            if valid {
+                Ok(())
+            } else {
+                Err(CheckpointError::InvalidOptimizer(reason))
+            }
+        };
+
+        check(
+            (0.0..=1.0).contains(&config.mutation_chance),
+            "mutation chance is out of range",
+        )?;
+
+        check(
+            config
+                .speciation
+                .as_ref()
+                .map_or(true, |speciation| speciation.threshold() > 0.0),
+            "speciation threshold is out of range",
+        )?;
+
+        check(
+            config.bounds.as_ref().map_or(true, ga::Bounds::is_valid),
+            "bounds are malformed",
+        )?;
+
+        check(
+            config.scaling.map_or(true, |scaling| scaling.is_valid()),
+            "scaling is out of range",
+        )?;
+
+        check(
+            (0.0..=1.0).contains(&config.immigrants),
+            "immigrants are out of range",
+        )?;
+
+        check(
+            config.restart.map_or(true, |restart| restart.is_valid()),
+            "restart is out of range",
+        )
+    }
+
+    /// Genetic algorithm breeding children in the steady-state mode (see
+    /// `Reproduction`) - built out of the genetic algorithm's settings, or
+    /// the default ones when birds are evolved by another optimizer.
+    ///
+    /// Steady-state mode doesn't evolve generations, so `generation` is
+    /// what the scaling's temperature schedule goes by instead; observers
+    /// get notified about each child, under the index it's going to have
+    /// among the birds.
+    pub(crate) fn breeder(
+        &self,
+        generation: usize,
+        observers: &Observers,
+    ) -> ga::GeneticAlgorithm<ga::RouletteWheelSelection> {
+        let mut ga = match self {
+            Self::GeneticAlgorithm(config) => config.build(),
+            _ => GeneticAlgorithmConfig::default().build(),
+        };
+
+        for observer in &observers.0 {
+            ga = ga.with_observer(observer.clone());
+        }
+
+        ga.with_generation(generation)
+    }
+
+    /// Makes sure the genetic algorithm doesn't use any of the settings
+    /// that only make sense when evolving generation by generation, which
+    /// the steady-state mode doesn't support.
+    pub(crate) fn validate_steady_state(&self) -> Result<(), CheckpointError> {
+        let Self::GeneticAlgorithm(config) = self else {
+            return Ok(());
+        };
+
+        if config.immigrants > 0.0 || config.restart.is_some() || config.speciation.is_some() {
+            return Err(CheckpointError::InvalidOptimizer(
+                "immigrants, restarts and speciation aren't supported in the steady-state mode",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Evolves population, notifying given observers - the genetic
+    /// algorithm notifies them about everything, while the other
+    /// optimizers only about generations starting and finishing (with
+    /// `generation` as the generation's number).
+    ///
+    /// `brains` creates random brains for the genetic algorithm's
+    /// immigrants and restarts.
+    pub(crate) fn evolve_observed(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[AnimalIndividual],
+        generation: usize,
+        observers: &Observers,
+        brains: impl ga::Generator + 'static,
+    ) -> (Vec<AnimalIndividual>, ga::Statistics) {
+        let optimizer: &mut dyn ga::Optimizer<AnimalIndividual> = match self {
+            Self::GeneticAlgorithm(config) => {
+                let mut ga = config.build().with_generator(brains);
+
+                for observer in &observers.0 {
+                    ga = ga.with_observer(observer.clone());
+                }
+
+                let result = ga::Optimizer::evolve(&mut ga, rng, population);
+
+                config.generation = ga.generation();
+                config.plateau = ga.plateau();
+                return result;
+            }
+            Self::DifferentialEvolution(de) => de,
+            Self::CmaEs(cma_es) => cma_es,
+        };
+
+        for observer in &observers.0 {
+            observer.generation_started(generation, population.len());
+        }
+
+        let (population, stats) = optimizer.evolve(rng, population);
+
+        for observer in &observers.0 {
+            observer.generation_finished(generation, &stats);
+        }
+
+        (population, stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Counter {
+        generations: Mutex<Vec<usize>>,
+        children: Mutex<usize>,
+        immigrants: Mutex<usize>,
+    }
+
+    impl ga::Observer for Counter {
+        fn generation_finished(&self, generation: usize, _: &ga::Statistics) {
+            self.generations.lock().unwrap().push(generation);
+        }
+
+        fn created(&self, _: usize, _: [usize; 2], _: &ga::Chromosome) {
+            *self.children.lock().unwrap() += 1;
+        }
+
+        fn immigrated(&self, _: usize, chromosome: &ga::Chromosome) {
+            assert!(chromosome.iter().all(|weight| weight.abs() <= 1.0));
+
+            *self.immigrants.lock().unwrap() += 1;
+        }
+    }
+
+    fn evolve(optimizer: Optimizer) -> Arc<Counter> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let counter = Arc::new(Counter::default());
+
+        let mut sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9)
+            .with_optimizer(optimizer)
+            .with_observer(counter.clone());
+
+        let params = StepParams {
+            generation_length: 200,
+            ..Default::default()
+        };
+
+        for _ in 0..402 {
+            sim.step(&mut rng, &params);
+        }
+
+        counter
+    }
+
+    #[test]
+    fn notifies_observers() {
+        let counter = evolve(Optimizer::default());
+
+        assert_eq!(*counter.generations.lock().unwrap(), [0, 1]);
+        assert_eq!(*counter.children.lock().unwrap(), 20);
+
+        // Other optimizers don't breed children one by one, but generations
+        // still get reported
+        let counter = evolve(Optimizer::CmaEs(ga::CmaEs::new(0.5)));
+
+        assert_eq!(*counter.generations.lock().unwrap(), [0, 1]);
+        assert_eq!(*counter.children.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn notifies_observers_in_steady_state() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let counter = Arc::new(Counter::default());
+
+        let mut sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9)
+            .with_reproduction(Reproduction::default())
+            .with_observer(counter.clone());
+
+        for animal in &mut sim.world.animals {
+            animal.energy = 20.0;
+        }
+
+        sim.step(&mut rng, &StepParams::default());
+
+        assert_eq!(sim.world().animals().len(), 20);
+        assert_eq!(*counter.children.lock().unwrap(), 10);
+    }
+
+    #[test]
+    fn brings_in_random_brains() {
+        let counter = evolve(Optimizer::GeneticAlgorithm(GeneticAlgorithmConfig {
+            immigrants: 0.3,
+            ..Default::default()
+        }));
+
+        // Three birds per generation
+        assert_eq!(*counter.immigrants.lock().unwrap(), 6);
+        assert_eq!(*counter.children.lock().unwrap(), 20);
+    }
+}
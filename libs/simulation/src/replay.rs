@@ -0,0 +1,343 @@
+use crate::*;
+use std::io;
+
+/// Version of the replay format; bumped whenever a change to any of the
+/// serialized structs makes older replays unreadable.
+pub const REPLAY_VERSION: u32 = 3;
+
+/// Recorded run: the state it started from, followed by changes made to
+/// the step parameters along the way.
+///
+/// Since the simulation is deterministic given its random number generator,
+/// that's all it takes to play the run back - no need to store any frames.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    simulation: Simulation,
+    rng: ChaCha8Rng,
+    inputs: Vec<Input>,
+    steps: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Input {
+    /// Step since which `params` are in effect
+    step: u64,
+    params: StepParams,
+}
+
+/// Steps a simulation while recording it into a `Replay`.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    replay: Replay,
+}
+
+/// Plays a `Replay` back.
+#[derive(Clone, Debug)]
+pub struct Player {
+    replay: Replay,
+    simulation: Simulation,
+    rng: ChaCha8Rng,
+    step: u64,
+
+    /// Snapshots taken at the beginning of each generation played so far,
+    /// so that seeking backwards doesn't have to start from scratch
+    keyframes: Vec<Keyframe>,
+}
+
+#[derive(Clone, Debug)]
+struct Keyframe {
+    step: u64,
+    simulation: Simulation,
+    rng: ChaCha8Rng,
+}
+
+impl Replay {
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn save(&self, writer: impl io::Write) -> Result<(), CheckpointError> {
+        serde_json::to_writer(writer, self)?;
+
+        Ok(())
+    }
+
+    pub fn load(reader: impl io::Read) -> Result<Self, CheckpointError> {
+        let mut replay: Self = checkpoint::read_versioned(reader, REPLAY_VERSION)?;
+
+        replay.validate()?;
+        replay.simulation.optimizer().validate()?;
+
+        if replay.simulation.reproduction().is_some() {
+            replay.simulation.optimizer().validate_steady_state()?;
+        }
+
+        replay.simulation.world.restore_ids();
+
+        Ok(replay)
+    }
+
+    /// Makes sure there are step parameters for each recorded step.
+    fn validate(&self) -> Result<(), CheckpointError> {
+        if self.steps == 0 {
+            return Ok(());
+        }
+
+        if self.inputs.first().map(|input| input.step) != Some(0) {
+            return Err(CheckpointError::InvalidReplay(
+                "inputs don't start at the first step",
+            ));
+        }
+
+        let ordered = self
+            .inputs
+            .windows(2)
+            .all(|inputs| inputs[0].step < inputs[1].step);
+
+        if !ordered || self.inputs.iter().any(|input| input.step >= self.steps) {
+            return Err(CheckpointError::InvalidReplay("inputs are out of order"));
+        }
+
+        Ok(())
+    }
+
+    fn params(&self, step: u64) -> &StepParams {
+        let idx = self.inputs.partition_point(|input| input.step <= step);
+
+        &self.inputs[idx.max(1) - 1].params
+    }
+}
+
+impl Recorder {
+    /// Starts recording from the current state of `simulation` and `rng`.
+    pub fn new(simulation: &Simulation, rng: &ChaCha8Rng) -> Self {
+        Self {
+            replay: Replay {
+                version: REPLAY_VERSION,
+                simulation: simulation.clone(),
+                rng: rng.clone(),
+                inputs: Vec::new(),
+                steps: 0,
+            },
+        }
+    }
+
+    pub fn step(
+        &mut self,
+        simulation: &mut Simulation,
+        rng: &mut ChaCha8Rng,
+        params: &StepParams,
+    ) -> Option<Statistics> {
+        let replay = &mut self.replay;
+
+        if replay.inputs.last().map(|input| &input.params) != Some(params) {
+            replay.inputs.push(Input {
+                step: replay.steps,
+                params: *params,
+            });
+        }
+
+        replay.steps += 1;
+        simulation.step(rng, params)
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+}
+
+impl Player {
+    pub fn new(replay: Replay) -> Self {
+        let simulation = replay.simulation.clone();
+        let rng = replay.rng.clone();
+
+        Self {
+            replay,
+            simulation,
+            rng,
+            step: 0,
+            keyframes: Vec::new(),
+        }
+    }
+
+    pub fn simulation(&self) -> &Simulation {
+        &self.simulation
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Number of steps played so far
+    pub fn position(&self) -> u64 {
+        self.step
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.replay.steps
+    }
+
+    /// Plays the next recorded step; does nothing once the replay is over.
+    pub fn step(&mut self) -> Option<Statistics> {
+        if self.is_finished() {
+            return None;
+        }
+
+        if self.keyframes.last().map_or(true, |keyframe| {
+            keyframe.simulation.generation < self.simulation.generation
+        }) {
+            self.keyframes.push(Keyframe {
+                step: self.step,
+                simulation: self.simulation.clone(),
+                rng: self.rng.clone(),
+            });
+        }
+
+        let params = *self.replay.params(self.step);
+
+        self.step += 1;
+        self.simulation.step(&mut self.rng, &params)
+    }
+
+    /// Jumps to the beginning of given generation (or to the end of the
+    /// replay, if it doesn't reach that far); returns whether the
+    /// generation has been reached.
+    pub fn seek(&mut self, generation: i32) -> bool {
+        if self.simulation.generation > generation
+            || (self.simulation.generation == generation && self.simulation.age > 0)
+        {
+            self.rewind(generation);
+        }
+
+        while self.simulation.generation < generation {
+            if self.is_finished() {
+                return false;
+            }
+
+            self.step();
+        }
+
+        true
+    }
+
+    fn rewind(&mut self, generation: i32) {
+        let idx = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.simulation.generation <= generation);
+
+        match self.keyframes.get(idx.max(1) - 1) {
+            Some(keyframe) => {
+                self.step = keyframe.step;
+                self.simulation = keyframe.simulation.clone();
+                self.rng = keyframe.rng.clone();
+            }
+            None => {
+                self.step = 0;
+                self.simulation = self.replay.simulation.clone();
+                self.rng = self.replay.rng.clone();
+            }
+        }
+
+        self.keyframes.truncate(idx.max(1) - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn params(generation_length: i32) -> StepParams {
+        StepParams {
+            generation_length,
+            ..Default::default()
+        }
+    }
+
+    fn record() -> (Recorder, Simulation, ChaCha8Rng) {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut sim = Simulation::random(&mut rng, 10, 20, 0.25, 3.9, 9);
+        let mut recorder = Recorder::new(&sim, &rng);
+
+        for step in 0..250 {
+            let params = if step < 120 { params(50) } else { params(40) };
+
+            recorder.step(&mut sim, &mut rng, &params);
+        }
+
+        (recorder, sim, rng)
+    }
+
+    fn save(sim: &Simulation) -> String {
+        serde_json::to_string(sim).unwrap()
+    }
+
+    #[test]
+    fn records_only_changes() {
+        let (recorder, _, _) = record();
+
+        assert_eq!(recorder.replay().inputs.len(), 2);
+        assert_eq!(recorder.replay().inputs[1].step, 120);
+        assert_eq!(recorder.replay().steps(), 250);
+    }
+
+    #[test]
+    fn plays_back_identically() {
+        let (recorder, sim, _) = record();
+
+        let mut replay = Vec::new();
+        recorder.replay().save(&mut replay).unwrap();
+
+        let mut player = Player::new(Replay::load(replay.as_slice()).unwrap());
+        while !player.is_finished() {
+            player.step();
+        }
+
+        assert!(player.is_finished());
+        assert_eq!(save(player.simulation()), save(&sim));
+    }
+
+    #[test]
+    fn rejects_missing_inputs() {
+        let (recorder, _, _) = record();
+
+        let mut replay = Vec::new();
+        recorder.replay().save(&mut replay).unwrap();
+
+        let replay: serde_json::Value = serde_json::from_slice(&replay).unwrap();
+
+        for inputs in [
+            serde_json::json!([]),
+            serde_json::json!([{ "step": 5, "params": replay["inputs"][0]["params"] }]),
+            serde_json::json!([replay["inputs"][1], replay["inputs"][0]]),
+        ] {
+            let mut replay = replay.clone();
+            replay["inputs"] = inputs;
+
+            assert!(matches!(
+                Replay::load(replay.to_string().as_bytes()),
+                Err(CheckpointError::InvalidReplay(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn seeks() {
+        let (recorder, _, _) = record();
+        let mut player = Player::new(recorder.replay().clone());
+
+        assert!(player.seek(3));
+        let forward = save(player.simulation());
+        assert_eq!(player.simulation().generation, 3);
+
+        // Backwards and then forwards again, through keyframes
+        assert!(player.seek(1));
+        assert_eq!(player.simulation().generation, 1);
+        assert!(player.seek(3));
+        assert_eq!(save(player.simulation()), forward);
+
+        assert!(!player.seek(100));
+        assert!(player.is_finished());
+    }
+}
@@ -0,0 +1,334 @@
+use crate::*;
+
+/// Steady-state mode: instead of the whole population being replaced at
+/// the end of each generation, birds live off the energy they get from
+/// food, reproduce (on the spot, with a nearby mate) once they have enough
+/// of it, and die when they run out of it or get too old.
+///
+/// Generations still get counted (every `generation_length` steps), but
+/// only for the sake of statistics - nobody gets reset.
+///
+/// Children are bred with the genetic algorithm's settings (see
+/// `Optimizer::breeder()`), out of the parent and a mate it selects among
+/// the birds around.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Reproduction {
+    /// Energy a bird needs to have in order to reproduce
+    pub threshold: f32,
+
+    /// Energy each bird is born with; it's paid by the parent
+    pub birth_energy: f32,
+
+    /// Energy each bird burns per step
+    pub metabolism: f32,
+
+    /// Number of steps after which birds die of old age, if any
+    pub lifespan: Option<u32>,
+
+    /// How close the mate has to be; children of birds without anybody
+    /// around are crossed over with themselves
+    pub mate_distance: f32,
+
+    /// Fewest birds there can be - once the population shrinks this much,
+    /// birds that die get replaced with random ones
+    pub min_animals: usize,
+
+    /// Most birds there can be - once the population grows this much,
+    /// nobody reproduces until somebody dies
+    pub max_animals: usize,
+}
+
+impl Default for Reproduction {
+    fn default() -> Self {
+        Self {
+            threshold: 10.0,
+            birth_energy: 5.0,
+            metabolism: 0.005,
+            lifespan: None,
+            mate_distance: 0.1,
+            min_animals: 10,
+            max_animals: 100,
+        }
+    }
+}
+
+impl Reproduction {
+    /// Lets birds live their lives for a single step: burn energy, age,
+    /// die and give birth (to children bred by `breeder`).
+    pub(crate) fn process(
+        &self,
+        rng: &mut dyn RngCore,
+        world: &mut World,
+        generation: u32,
+        breeder: &ga::GeneticAlgorithm<ga::RouletteWheelSelection>,
+    ) {
+        for animal in &mut world.animals {
+            animal.energy -= self.metabolism;
+            animal.age += 1;
+        }
+
+        self.process_deaths(rng, world, generation);
+        self.process_births(rng, world, generation, breeder);
+    }
+
+    /// Gives all birds energy they'd have if they've just been born.
+    pub(crate) fn reset_energy(&self, world: &mut World) {
+        for animal in &mut world.animals {
+            animal.energy = self.birth_energy;
+        }
+    }
+
+    fn process_deaths(&self, rng: &mut dyn RngCore, world: &mut World, generation: u32) {
+        let is_dead = |animal: &Animal| {
+            animal.energy < 0.0
+                || self
+                    .lifespan
+                    .map_or(false, |lifespan| animal.age >= lifespan)
+        };
+
+        let mut alive = world
+            .animals
+            .iter()
+            .filter(|animal| !is_dead(animal))
+            .count();
+        let mut idx = 0;
+
+        while idx < world.animals.len() {
+            if !is_dead(&world.animals[idx]) {
+                idx += 1;
+                continue;
+            }
+
+            if alive >= self.min_animals {
+                world.animals.swap_remove(idx);
+                continue;
+            }
+
+            // Population is too small already, so instead of just dying,
+            // the bird gets replaced with a random one
+            let dead = &world.animals[idx];
+            let mut animal = Animal::random(rng, dead.eye.clone(), &dead.brain.hidden());
+
+            animal.position = world.arena.spawn(rng);
+            animal.energy = self.birth_energy;
+            world.register(&mut animal, &[], generation);
+
+            world.animals[idx] = animal;
+            alive += 1;
+            idx += 1;
+        }
+    }
+
+    fn process_births(
+        &self,
+        rng: &mut dyn RngCore,
+        world: &mut World,
+        generation: u32,
+        breeder: &ga::GeneticAlgorithm<ga::RouletteWheelSelection>,
+    ) {
+        let parents = world.animals.len();
+
+        // (built only once somebody's ready to give birth, since most of
+        // the time nobody is)
+        let mut population = None;
+
+        for parent in 0..parents {
+            if world.animals.len() >= self.max_animals {
+                break;
+            }
+
+            if world.animals[parent].energy < self.threshold {
+                continue;
+            }
+
+            let population: &Vec<_> = population.get_or_insert_with(|| {
+                world.animals[..parents]
+                    .iter()
+                    .map(AnimalIndividual::from_animal)
+                    .collect()
+            });
+
+            let mates = self.find_mates(&world.animals[..parents], parent);
+
+            let (child, mate): (AnimalIndividual, _) =
+                breeder.breed_with(rng, population, parent, &mates, world.animals.len());
+
+            let mate = world.animals[mate].id;
+            let parent = &mut world.animals[parent];
+            parent.energy -= self.birth_energy;
+
+            let mut child = child.into_animal(rng, parent.eye.clone(), &parent.brain.hidden());
+
+            child.position = parent.position;
+            child.energy = self.birth_energy;
+
+            let parents = [parent.id, mate];
+
+            world
+                .arena
+                .confine(&mut child.position, &mut child.rotation);
+
+            world.register(&mut child, &parents, generation);
+
+            world.animals.push(child);
+        }
+    }
+
+    /// Returns birds within `mate_distance` of the parent.
+    fn find_mates(&self, animals: &[Animal], parent: usize) -> Vec<usize> {
+        let position = animals[parent].position;
+
+        (0..animals.len())
+            .filter(|&idx| idx != parent)
+            .filter(|&idx| na::distance(&position, &animals[idx].position) <= self.mate_distance)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn world(rng: &mut ChaCha8Rng, animals: i32) -> World {
+        World::random(rng, animals, 10, 0.25, 3.9, 3)
+    }
+
+    fn breeder() -> ga::GeneticAlgorithm<ga::RouletteWheelSelection> {
+        Optimizer::default().breeder(0, &Observers::default())
+    }
+
+    #[test]
+    fn gives_birth_next_to_parent() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = world(&mut rng, 3);
+        let reproduction = Reproduction::default();
+
+        reproduction.reset_energy(&mut world);
+        world.animals[0].energy = 12.0;
+
+        // Mate is close enough to the parent, while the third bird isn't
+        // (even though it's the fittest one)
+        world.animals[0].position = na::Point2::new(0.5, 0.5);
+        world.animals[1].position = na::Point2::new(0.55, 0.5);
+        world.animals[2].position = na::Point2::new(0.1, 0.1);
+        world.animals[1].satiation = 1.0;
+        world.animals[2].satiation = 5.0;
+
+        reproduction.process(&mut rng, &mut world, 0, &breeder());
+
+        assert_eq!(world.animals.len(), 4);
+        approx::assert_relative_eq!(world.animals[0].energy, 7.0 - reproduction.metabolism);
+        approx::assert_relative_eq!(world.animals[3].energy, 5.0);
+        assert_eq!(world.animals[3].position, na::Point2::new(0.5, 0.5));
+        assert_eq!(world.animals[3].age, 0);
+        assert_eq!(world.animals[3].id, 3);
+        assert_eq!(world.animals[3].parents, [0, 1]);
+
+        // Child's genes come from its parents (mutation aside)
+        let child = world.animals[3].as_chromosome();
+        let parent_a = world.animals[0].as_chromosome();
+        let parent_b = world.animals[1].as_chromosome();
+
+        let inherited = child
+            .iter()
+            .zip(parent_a.iter().zip(parent_b.iter()))
+            .filter(|(gene, (a, b))| gene == a || gene == b)
+            .count();
+
+        assert!(inherited > child.len() * 9 / 10);
+    }
+
+    #[test]
+    fn breeds_with_optimizer_settings() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = world(&mut rng, 10);
+        let reproduction = Reproduction::default();
+
+        let breeder = Optimizer::GeneticAlgorithm(GeneticAlgorithmConfig {
+            mutation_chance: 1.0,
+            bounds: Some(ga::Bounds::uniform(-0.1, 0.1, ga::Repair::Clamp)),
+            ..Default::default()
+        })
+        .breeder(0, &Observers::default());
+
+        reproduction.reset_energy(&mut world);
+
+        for animal in &mut world.animals {
+            animal.energy = 20.0;
+        }
+
+        reproduction.process(&mut rng, &mut world, 0, &breeder);
+
+        assert_eq!(world.animals.len(), 20);
+
+        for child in &world.animals[10..] {
+            assert!(child.as_chromosome().iter().all(|w| w.abs() <= 0.1));
+        }
+    }
+
+    #[test]
+    fn respects_population_caps() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = world(&mut rng, 5);
+
+        let reproduction = Reproduction {
+            min_animals: 4,
+            max_animals: 6,
+            ..Default::default()
+        };
+
+        reproduction.reset_energy(&mut world);
+
+        for animal in &mut world.animals {
+            animal.energy = 20.0;
+        }
+
+        reproduction.process(&mut rng, &mut world, 0, &breeder());
+        assert_eq!(world.animals.len(), 6);
+
+        for animal in &mut world.animals {
+            animal.energy = -1.0;
+        }
+
+        // Everybody starves, but four birds get replaced with new ones
+        reproduction.process(&mut rng, &mut world, 0, &breeder());
+        assert_eq!(world.animals.len(), 4);
+        assert!(world.animals.iter().all(|animal| animal.energy == 5.0));
+    }
+
+    #[test]
+    fn runs_continuously() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let mut sim = Simulation::new(world(&mut rng, 20)).with_reproduction(Reproduction {
+            threshold: 2.0,
+            birth_energy: 1.0,
+            metabolism: 0.002,
+            ..Default::default()
+        });
+
+        let params = StepParams {
+            generation_length: 100,
+            ..Default::default()
+        };
+
+        for _ in 0..1010 {
+            sim.step(&mut rng, &params);
+        }
+
+        assert_eq!(sim.generation, 10);
+        assert_eq!(sim.history().len(), 10);
+
+        let animals = sim.world().animals();
+
+        assert!((10..=100).contains(&animals.len()));
+
+        // Nobody gets reset when a generation ends, so some birds have
+        // outlived it
+        assert!(animals
+            .iter()
+            .any(|animal| animal.age() > params.generation_length as u32));
+    }
+}
@@ -0,0 +1,962 @@
+use crate::*;
+use rand::SeedableRng;
+use std::{f32::consts::TAU, fmt};
+
+/// Declarative description of an experiment - everything that's needed to
+/// start the same simulation over and over again, in TOML or JSON:
+///
+/// ```toml
+/// seed = 42
+/// stop = { kind = "any", conditions = [{ kind = "max-generations", generations = 500 }, { kind = "stagnation", generations = 50 }] }
+///
+/// [world]
+/// width = 2.0
+/// height = 1.0
+/// boundary = "bounce"
+/// obstacles = [{ position = [1.0, 0.5], radius = 0.2 }]
+///
+/// [food]
+/// count = 40
+/// kinds = [{ nutrition = 1.0, ratio = 3.0 }, { nutrition = -2.0, ratio = 1.0 }]
+/// speed = 0.001
+/// spawn = { kind = "patches", patches = 3, radius = 0.1, capacity = 20.0, regrowth = 0.05 }
+///
+/// [collisions]
+/// body_radius = 0.01
+/// contention = "nearest"
+///
+/// [population]
+/// animals = 60
+///
+/// [eye]
+/// cells = 9
+///
+/// [brain]
+/// hidden = [18, 9]
+///
+/// [optimizer]
+/// kind = "genetic-algorithm"
+/// mutation_chance = 0.01
+/// mutation_coeff = 0.3
+/// weight_limit = 2.0
+/// weight_repair = "clamp"
+/// scaling = { kind = "rank" }
+/// ```
+///
+/// Everything except for `seed` is optional and defaults to what the
+/// frontend starts with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub seed: u64,
+
+    #[serde(default)]
+    pub world: Arena,
+
+    #[serde(default)]
+    pub food: FoodConfig,
+
+    #[serde(default)]
+    pub collisions: Collisions,
+
+    #[serde(default)]
+    pub population: PopulationSettings,
+
+    /// When given, birds reproduce and die on their own instead of being
+    /// evolved generation by generation
+    #[serde(default)]
+    pub reproduction: Option<Reproduction>,
+
+    #[serde(default)]
+    pub eye: EyeSettings,
+
+    #[serde(default)]
+    pub brain: BrainSettings,
+
+    #[serde(default)]
+    pub optimizer: OptimizerSettings,
+
+    /// When evolution is done - used by headless runs (see
+    /// `Simulation::run()`)
+    #[serde(default)]
+    pub stop: Option<ga::StopCondition>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PopulationSettings {
+    pub animals: i32,
+    pub generation_length: i32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EyeSettings {
+    pub fov_range: f32,
+    pub fov_angle: f32,
+    pub cells: usize,
+}
+
+/// Shape of the brain and how its answers are turned into movement.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BrainSettings {
+    /// Sizes of the hidden layers; defaults to `Brain::default_hidden()`
+    pub hidden: Option<Vec<usize>>,
+
+    pub speed_min: f32,
+    pub speed_max: f32,
+    pub speed_accel: f32,
+    pub rotation_accel: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum OptimizerSettings {
+    GeneticAlgorithm {
+        mutation_chance: f32,
+        mutation_coeff: f32,
+        speciation_threshold: Option<f32>,
+
+        /// Brain weights are kept within `[-weight_limit, weight_limit]`,
+        /// using `weight_repair` to bring back the ones that stray
+        #[serde(default)]
+        weight_limit: Option<f32>,
+
+        #[serde(default)]
+        weight_repair: ga::Repair,
+
+        /// Transformation of satiation before selection, e.g. `{ kind =
+        /// "rank" }` or `{ kind = "power", exponent = 2.0 }`
+        #[serde(default)]
+        scaling: Option<ga::Scaling>,
+
+        /// Fraction of each generation that's made of random birds
+        #[serde(default)]
+        immigrants: f32,
+
+        /// Replaces the population with random birds once it's stopped
+        /// improving, e.g. `{ stagnation = 20, fraction = 0.8, elites = 2 }`
+        #[serde(default)]
+        restart: Option<ga::Restart>,
+    },
+    DifferentialEvolution {
+        strategy: ga::DifferentialStrategy,
+        weight: f32,
+        crossover: f32,
+    },
+    CmaEs {
+        sigma: f32,
+    },
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    /// Scenario is not a valid TOML / JSON document
+    Syntax(String),
+
+    /// Given field is missing, has a wrong type or an invalid value
+    Invalid { field: String, message: String },
+}
+
+impl Scenario {
+    /// Parses scenario written in either JSON or TOML.
+    pub fn parse(scenario: &str) -> Result<Self, ScenarioError> {
+        if scenario.trim_start().starts_with('{') {
+            Self::from_json(scenario)
+        } else {
+            Self::from_toml(scenario)
+        }
+    }
+
+    pub fn from_toml(scenario: &str) -> Result<Self, ScenarioError> {
+        let mut deserializer = toml::Deserializer::new(scenario);
+
+        let scenario: Self =
+            serde_path_to_error::deserialize(&mut deserializer).map_err(ScenarioError::from)?;
+
+        scenario.validate()?;
+
+        Ok(scenario)
+    }
+
+    pub fn from_json(scenario: &str) -> Result<Self, ScenarioError> {
+        let mut deserializer = serde_json::Deserializer::from_str(scenario);
+
+        let scenario: Self =
+            serde_path_to_error::deserialize(&mut deserializer).map_err(ScenarioError::from)?;
+
+        deserializer
+            .end()
+            .map_err(|err| ScenarioError::Syntax(err.to_string()))?;
+
+        scenario.validate()?;
+
+        Ok(scenario)
+    }
+
+    /// Creates the simulation described by this scenario, together with a
+    /// generator that should be used to step it.
+    pub fn build(&self) -> (Simulation, ChaCha8Rng) {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let eye = Eye::default(self.eye.fov_range, self.eye.fov_angle, self.eye.cells)
+            .with_channels(self.food.kinds.len());
+
+        let hidden = self
+            .brain
+            .hidden
+            .clone()
+            .unwrap_or_else(|| Brain::default_hidden(&eye));
+
+        let world = World::populate(
+            &mut rng,
+            self.world.clone(),
+            self.food.clone(),
+            self.population.animals,
+            &eye,
+            &hidden,
+        );
+
+        let world = world.with_collisions(self.collisions.clone());
+        let simulation = Simulation::new(world).with_optimizer(self.optimizer.build());
+
+        let simulation = match &self.reproduction {
+            Some(reproduction) => simulation.with_reproduction(reproduction.clone()),
+            None => simulation,
+        };
+
+        (simulation, rng)
+    }
+
+    /// Parameters the simulation should be stepped with.
+    pub fn params(&self) -> StepParams {
+        StepParams {
+            speed_min: self.brain.speed_min,
+            speed_max: self.brain.speed_max,
+            speed_accel: self.brain.speed_accel,
+            rotation_accel: self.brain.rotation_accel,
+            generation_length: self.population.generation_length,
+            fov_range: self.eye.fov_range,
+            fov_angle: self.eye.fov_angle,
+            cells: self.eye.cells,
+        }
+    }
+
+    fn validate(&self) -> Result<(), ScenarioError> {
+        check(self.world.width > 0.0, "world.width", "must be positive")?;
+        check(self.world.height > 0.0, "world.height", "must be positive")?;
+
+        for (idx, obstacle) in self.world.obstacles.iter().enumerate() {
+            check(
+                obstacle.radius > 0.0,
+                &format!("world.obstacles[{}].radius", idx),
+                "must be positive",
+            )?;
+        }
+
+        self.validate_food()?;
+
+        if let Some(radius) = self.collisions.body_radius {
+            check(radius > 0.0, "collisions.body_radius", "must be positive")?;
+        }
+
+        check(
+            self.population.animals > 0,
+            "population.animals",
+            "must be positive",
+        )?;
+
+        check(
+            self.population.generation_length > 0,
+            "population.generation_length",
+            "must be positive",
+        )?;
+
+        if let Some(reproduction) = &self.reproduction {
+            self.validate_reproduction(reproduction)?;
+        }
+
+        check(
+            self.eye.fov_range > 0.0,
+            "eye.fov_range",
+            "must be positive",
+        )?;
+
+        check(
+            self.eye.fov_angle > 0.0 && self.eye.fov_angle <= TAU,
+            "eye.fov_angle",
+            "must be within (0, 2 * PI]",
+        )?;
+
+        check(self.eye.cells > 0, "eye.cells", "must be positive")?;
+
+        if let Some(hidden) = &self.brain.hidden {
+            for (idx, &neurons) in hidden.iter().enumerate() {
+                check(
+                    neurons > 0,
+                    &format!("brain.hidden[{}]", idx),
+                    "must be positive",
+                )?;
+            }
+        }
+
+        check(
+            self.brain.speed_min >= 0.0,
+            "brain.speed_min",
+            "must not be negative",
+        )?;
+
+        check(
+            self.brain.speed_max >= self.brain.speed_min,
+            "brain.speed_max",
+            "must not be smaller than `brain.speed_min`",
+        )?;
+
+        check(
+            self.brain.speed_accel >= 0.0,
+            "brain.speed_accel",
+            "must not be negative",
+        )?;
+
+        check(
+            self.brain.rotation_accel >= 0.0,
+            "brain.rotation_accel",
+            "must not be negative",
+        )?;
+
+        match self.optimizer {
+            OptimizerSettings::GeneticAlgorithm {
+                mutation_chance,
+                mutation_coeff,
+                speciation_threshold,
+                weight_limit,
+                scaling,
+                immigrants,
+                restart,
+                ..
+            } => {
+                check(
+                    (0.0..=1.0).contains(&mutation_chance),
+                    "optimizer.mutation_chance",
+                    "must be within [0, 1]",
+                )?;
+
+                check(
+                    mutation_coeff >= 0.0,
+                    "optimizer.mutation_coeff",
+                    "must not be negative",
+                )?;
+
+                if let Some(threshold) = speciation_threshold {
+                    check(
+                        threshold > 0.0,
+                        "optimizer.speciation_threshold",
+                        "must be positive",
+                    )?;
+                }
+
+                if let Some(limit) = weight_limit {
+                    check(limit > 0.0, "optimizer.weight_limit", "must be positive")?;
+                }
+
+                if let Some(scaling) = scaling {
+                    check(
+                        scaling.is_valid(),
+                        "optimizer.scaling",
+                        "has out-of-range parameters",
+                    )?;
+                }
+
+                check(
+                    (0.0..=1.0).contains(&immigrants),
+                    "optimizer.immigrants",
+                    "must be within [0, 1]",
+                )?;
+
+                if let Some(restart) = restart {
+                    check(
+                        restart.is_valid(),
+                        "optimizer.restart",
+                        "has out-of-range parameters",
+                    )?;
+                }
+            }
+
+            OptimizerSettings::DifferentialEvolution {
+                weight, crossover, ..
+            } => {
+                check(
+                    (0.0..=2.0).contains(&weight),
+                    "optimizer.weight",
+                    "must be within [0, 2]",
+                )?;
+
+                check(
+                    (0.0..=1.0).contains(&crossover),
+                    "optimizer.crossover",
+                    "must be within [0, 1]",
+                )?;
+
+                check(
+                    self.population.animals >= 4,
+                    "population.animals",
+                    "differential evolution needs at least 4 animals",
+                )?;
+            }
+
+            OptimizerSettings::CmaEs { sigma } => {
+                check(sigma > 0.0, "optimizer.sigma", "must be positive")?;
+            }
+        }
+
+        if let Some(stop) = &self.stop {
+            check(stop.is_valid(), "stop", "has out-of-range parameters")?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_reproduction(&self, reproduction: &Reproduction) -> Result<(), ScenarioError> {
+        check(
+            reproduction.birth_energy > 0.0,
+            "reproduction.birth_energy",
+            "must be positive",
+        )?;
+
+        check(
+            reproduction.threshold >= reproduction.birth_energy,
+            "reproduction.threshold",
+            "must not be smaller than `reproduction.birth_energy`",
+        )?;
+
+        check(
+            reproduction.metabolism >= 0.0,
+            "reproduction.metabolism",
+            "must not be negative",
+        )?;
+
+        if let Some(lifespan) = reproduction.lifespan {
+            check(lifespan > 0, "reproduction.lifespan", "must be positive")?;
+        }
+
+        check(
+            reproduction.mate_distance >= 0.0,
+            "reproduction.mate_distance",
+            "must not be negative",
+        )?;
+
+        // (children are bred with the genetic algorithm's settings, which
+        // other optimizers don't have)
+        check(
+            matches!(self.optimizer, OptimizerSettings::GeneticAlgorithm { .. }),
+            "optimizer.kind",
+            "must be `genetic-algorithm` when `reproduction` is given",
+        )?;
+
+        // (the other way around, immigrants, restarts and speciation only
+        // make sense when birds are evolved generation by generation)
+        if let OptimizerSettings::GeneticAlgorithm {
+            speciation_threshold,
+            immigrants,
+            restart,
+            ..
+        } = &self.optimizer
+        {
+            check(
+                speciation_threshold.is_none(),
+                "optimizer.speciation_threshold",
+                "isn't supported together with `reproduction`",
+            )?;
+
+            check(
+                *immigrants == 0.0,
+                "optimizer.immigrants",
+                "isn't supported together with `reproduction`",
+            )?;
+
+            check(
+                restart.is_none(),
+                "optimizer.restart",
+                "isn't supported together with `reproduction`",
+            )?;
+        }
+
+        check(
+            reproduction.min_animals > 0,
+            "reproduction.min_animals",
+            "must be positive",
+        )?;
+
+        check(
+            reproduction.max_animals >= reproduction.min_animals,
+            "reproduction.max_animals",
+            "must not be smaller than `reproduction.min_animals`",
+        )?;
+
+        check(
+            (reproduction.min_animals..=reproduction.max_animals)
+                .contains(&(self.population.animals as usize)),
+            "population.animals",
+            "must be within `reproduction.min_animals` and `reproduction.max_animals`",
+        )
+    }
+
+    fn validate_food(&self) -> Result<(), ScenarioError> {
+        check(
+            !self.food.kinds.is_empty(),
+            "food.kinds",
+            "must not be empty",
+        )?;
+
+        for (idx, kind) in self.food.kinds.iter().enumerate() {
+            check(
+                kind.ratio >= 0.0,
+                &format!("food.kinds[{}].ratio", idx),
+                "must not be negative",
+            )?;
+        }
+
+        check(
+            self.food.kinds.iter().any(|kind| kind.ratio > 0.0),
+            "food.kinds",
+            "must have at least one kind with positive ratio",
+        )?;
+
+        match self.food.spawn {
+            FoodSpawn::Uniform => {}
+
+            FoodSpawn::Clusters { clusters, spread } => {
+                check(clusters > 0, "food.spawn.clusters", "must be positive")?;
+                check(spread >= 0.0, "food.spawn.spread", "must not be negative")?;
+            }
+
+            FoodSpawn::Patches {
+                patches,
+                radius,
+                capacity,
+                regrowth,
+            } => {
+                check(patches > 0, "food.spawn.patches", "must be positive")?;
+                check(radius > 0.0, "food.spawn.radius", "must be positive")?;
+
+                check(capacity >= 1.0, "food.spawn.capacity", "must be at least 1")?;
+
+                check(
+                    regrowth >= 0.0,
+                    "food.spawn.regrowth",
+                    "must not be negative",
+                )?;
+            }
+        }
+
+        check(self.food.speed >= 0.0, "food.speed", "must not be negative")?;
+
+        if let Some(lifetime) = self.food.lifetime {
+            check(lifetime > 0, "food.lifetime", "must be positive")?;
+        }
+
+        if let Some(seasons) = &self.food.seasons {
+            check(
+                seasons.length > 0,
+                "food.seasons.length",
+                "must be positive",
+            )?;
+
+            check(
+                (0.0..=1.0).contains(&seasons.amplitude),
+                "food.seasons.amplitude",
+                "must be within [0, 1]",
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn check(condition: bool, field: &str, message: &str) -> Result<(), ScenarioError> {
+    if condition {
+        Ok(())
+    } else {
+        Err(ScenarioError::Invalid {
+            field: field.into(),
+            message: message.into(),
+        })
+    }
+}
+
+impl OptimizerSettings {
+    pub fn build(&self) -> Optimizer {
+        match *self {
+            Self::GeneticAlgorithm {
+                mutation_chance,
+                mutation_coeff,
+                speciation_threshold,
+                weight_limit,
+                weight_repair,
+                scaling,
+                immigrants,
+                restart,
+            } => Optimizer::GeneticAlgorithm(GeneticAlgorithmConfig {
+                mutation_chance,
+                mutation_coeff,
+                speciation: speciation_threshold.map(ga::Speciation::new),
+                bounds: weight_limit.map(|limit| ga::Bounds::uniform(-limit, limit, weight_repair)),
+                scaling,
+                immigrants,
+                restart,
+                plateau: ga::Plateau::default(),
+                generation: 0,
+            }),
+
+            Self::DifferentialEvolution {
+                strategy,
+                weight,
+                crossover,
+            } => Optimizer::DifferentialEvolution(ga::DifferentialEvolution::new(
+                strategy, weight, crossover,
+            )),
+
+            Self::CmaEs { sigma } => Optimizer::CmaEs(ga::CmaEs::new(sigma)),
+        }
+    }
+}
+
+impl Default for PopulationSettings {
+    fn default() -> Self {
+        Self {
+            animals: 60,
+            generation_length: StepParams::default().generation_length,
+        }
+    }
+}
+
+impl Default for EyeSettings {
+    fn default() -> Self {
+        let params = StepParams::default();
+
+        Self {
+            fov_range: params.fov_range,
+            fov_angle: params.fov_angle,
+            cells: params.cells,
+        }
+    }
+}
+
+impl Default for BrainSettings {
+    fn default() -> Self {
+        let params = StepParams::default();
+
+        Self {
+            hidden: None,
+            speed_min: params.speed_min,
+            speed_max: params.speed_max,
+            speed_accel: params.speed_accel,
+            rotation_accel: params.rotation_accel,
+        }
+    }
+}
+
+impl Default for OptimizerSettings {
+    fn default() -> Self {
+        let config = GeneticAlgorithmConfig::default();
+
+        Self::GeneticAlgorithm {
+            mutation_chance: config.mutation_chance,
+            mutation_coeff: config.mutation_coeff,
+            speciation_threshold: None,
+            weight_limit: None,
+            weight_repair: ga::Repair::default(),
+            scaling: None,
+            immigrants: 0.0,
+            restart: None,
+        }
+    }
+}
+
+impl<E: fmt::Display> From<serde_path_to_error::Error<E>> for ScenarioError {
+    fn from(err: serde_path_to_error::Error<E>) -> Self {
+        let field = err.path().to_string();
+        let message = err.into_inner().to_string();
+
+        if field == "." {
+            Self::Syntax(message)
+        } else {
+            Self::Invalid { field, message }
+        }
+    }
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(message) => write!(f, "scenario is malformed: {}", message),
+            Self::Invalid { field, message } => write!(f, "`{}` {}", field, message),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(result: Result<Scenario, ScenarioError>) -> String {
+        match result {
+            Err(ScenarioError::Invalid { field, .. }) => field,
+            other => panic!("expected invalid field, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_toml_and_json() {
+        let toml = Scenario::parse(
+            r#"
+            seed = 42
+
+            [world]
+            width = 2.0
+            boundary = "bounce"
+            obstacles = [{ position = [1.0, 0.5], radius = 0.2 }]
+
+            [food]
+            lifetime = 100
+            kinds = [{ nutrition = 1.0, ratio = 2.0 }, { nutrition = -1.0, ratio = 1.0 }]
+            spawn = { kind = "clusters", clusters = 3, spread = 0.05 }
+
+            [collisions]
+            contention = "split"
+
+            [brain]
+            hidden = [18, 9]
+
+            [optimizer]
+            kind = "cma-es"
+            sigma = 0.5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(toml.seed, 42);
+        assert_eq!(toml.world.width, 2.0);
+        assert_eq!(toml.world.height, 1.0);
+        assert_eq!(toml.world.boundary, Boundary::Bounce);
+        assert_eq!(toml.world.obstacles.len(), 1);
+        assert_eq!(toml.food.count, 40);
+        assert_eq!(toml.food.lifetime, Some(100));
+        assert_eq!(toml.food.kinds[1].nutrition, -1.0);
+        assert_eq!(
+            toml.food.spawn,
+            FoodSpawn::Clusters {
+                clusters: 3,
+                spread: 0.05
+            }
+        );
+        assert_eq!(toml.collisions.body_radius, None);
+        assert_eq!(toml.collisions.contention, Contention::Split);
+        assert!(toml.reproduction.is_none());
+        assert_eq!(toml.brain.hidden, Some(vec![18, 9]));
+        assert!(matches!(toml.optimizer, OptimizerSettings::CmaEs { .. }));
+
+        let json = Scenario::parse(&serde_json::to_string(&toml).unwrap()).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&json).unwrap(),
+            serde_json::to_string(&toml).unwrap()
+        );
+    }
+
+    #[test]
+    fn builds_reproducibly() {
+        let scenario = Scenario::parse(
+            r#"
+            seed = 1
+
+            [world]
+            width = 1.5
+            height = 1.0
+            obstacles = [{ position = [0.75, 0.5], radius = 0.25 }]
+
+            [food]
+            count = 60
+            kinds = [{ nutrition = 1.0, ratio = 3.0 }, { nutrition = -1.0, ratio = 1.0 }]
+
+            [population]
+            animals = 10
+            generation_length = 150
+
+            [brain]
+            hidden = [4, 3]
+
+            [optimizer]
+            kind = "genetic-algorithm"
+            mutation_chance = 0.1
+            mutation_coeff = 0.5
+            weight_limit = 1.0
+            weight_repair = "reflect"
+            scaling = { kind = "boltzmann", temperature = 5.0, cooling = 0.5, min_temperature = 1.0 }
+            immigrants = 0.2
+            restart = { stagnation = 1, fraction = 0.5, elites = 2 }
+            "#,
+        )
+        .unwrap();
+
+        let run = || {
+            let (mut sim, mut rng) = scenario.build();
+
+            for _ in 0..310 {
+                sim.step(&mut rng, &scenario.params());
+            }
+
+            sim
+        };
+
+        let sim = run();
+
+        assert_eq!(sim.generation, 2);
+        assert_eq!(sim.world().animals()[0].brain().hidden(), [4, 3]);
+        assert_eq!(sim.world().animals()[0].eye().channels(), 2);
+        assert_eq!(sim.history()[0].meals.len(), 2);
+
+        for stats in sim.history() {
+            assert!(stats.diversity.pairwise_distance() > 0.0);
+            assert!(stats.diversity.gene_entropy() > 0.0);
+            assert!(stats.behavioural_diversity > 0.0);
+        }
+
+        // Children are bred out of the fittest birds, so they're less
+        // diverse than their random ancestors
+        assert!(
+            sim.history()[1].diversity.pairwise_distance()
+                < sim.history()[0].diversity.pairwise_distance()
+        );
+
+        match sim.optimizer() {
+            Optimizer::GeneticAlgorithm(config) => assert_eq!(config.generation, 2),
+            other => panic!("expected genetic algorithm, got: {:?}", other),
+        }
+        assert_eq!(
+            serde_json::to_string(&sim).unwrap(),
+            serde_json::to_string(&run()).unwrap()
+        );
+
+        for animal in sim.world().animals() {
+            assert!((0.0..=1.5).contains(&animal.position().x));
+            assert!((0.0..=1.0).contains(&animal.position().y));
+            assert!(animal.as_chromosome().iter().all(|w| w.abs() <= 1.0));
+        }
+    }
+
+    #[test]
+    fn runs_until_stopped() {
+        let scenario = Scenario::parse(
+            r#"
+            seed = 1
+            stop = { kind = "any", conditions = [{ kind = "max-generations", generations = 3 }, { kind = "target-fitness", fitness = 1000.0 }] }
+
+            [population]
+            animals = 10
+            generation_length = 100
+            "#,
+        )
+        .unwrap();
+
+        let (mut sim, mut rng) = scenario.build();
+        let mut controller = ga::RunController::new(scenario.stop.clone().unwrap());
+        let mut generations = Vec::new();
+
+        let reason = sim.run(&mut rng, &scenario.params(), &mut controller, |stats| {
+            generations.push(stats.generation);
+        });
+
+        assert_eq!(reason, ga::StopReason::MaxGenerations { generations: 3 });
+        assert_eq!(generations, [0, 1, 2]);
+        assert_eq!(sim.generation, 3);
+    }
+
+    #[test]
+    fn points_at_offending_field() {
+        assert_eq!(
+            field(Scenario::parse("seed = 1\n[world]\nwidth = \"wide\"")),
+            "world.width"
+        );
+
+        assert_eq!(
+            field(Scenario::parse("seed = 1\n[eye]\ncolour = \"red\"")),
+            "eye.colour"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(
+                "seed = 1\n[world]\nobstacles = [{ position = [0.5, 0.5], radius = 0.1 }, { position = [0.2, 0.2], radius = -1.0 }]"
+            )),
+            "world.obstacles[1].radius"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(
+                "seed = 1\n[food.spawn]\nkind = \"patches\"\npatches = 2\nradius = 0.1\ncapacity = 0.5\nregrowth = 0.1"
+            )),
+            "food.spawn.capacity"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(r#"{ "seed": 1, "eye": { "cells": 0 } }"#)),
+            "eye.cells"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(
+                "seed = 1\n[optimizer]\nkind = \"differential-evolution\"\nstrategy = \"rand1-bin\"\nweight = 0.5\ncrossover = 1.5"
+            )),
+            "optimizer.crossover"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(
+                "seed = 1\n[optimizer]\nkind = \"genetic-algorithm\"\nmutation_chance = 0.01\nmutation_coeff = 0.3\nweight_limit = 0.0"
+            )),
+            "optimizer.weight_limit"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(
+                "seed = 1\n[optimizer]\nkind = \"genetic-algorithm\"\nmutation_chance = 0.01\nmutation_coeff = 0.3\nrestart = { stagnation = 10, fraction = 0.0, elites = 1 }"
+            )),
+            "optimizer.restart"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(
+                "seed = 1\n[reproduction]\nmin_animals = 70\nmax_animals = 80"
+            )),
+            "population.animals"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(
+                "seed = 1\n[reproduction]\n[optimizer]\nkind = \"cma-es\"\nsigma = 0.5"
+            )),
+            "optimizer.kind"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(
+                "seed = 1\n[reproduction]\n[optimizer]\nkind = \"genetic-algorithm\"\nmutation_chance = 0.01\nmutation_coeff = 0.3\nimmigrants = 0.1"
+            )),
+            "optimizer.immigrants"
+        );
+
+        assert_eq!(
+            field(Scenario::parse(
+                "seed = 1\nstop = { kind = \"any\", conditions = [{ kind = \"stagnation\", generations = 0 }] }"
+            )),
+            "stop"
+        );
+
+        assert!(matches!(
+            Scenario::parse("seed = "),
+            Err(ScenarioError::Syntax(_))
+        ));
+    }
+}
@@ -0,0 +1,23 @@
+use crate::*;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Statistics {
+    /// Generation these statistics describe, i.e. the one that has just
+    /// finished
+    pub generation: i32,
+    pub ga: ga::Statistics,
+
+    /// How many pieces of each kind of food have been eaten
+    #[serde(default)]
+    pub meals: Vec<usize>,
+
+    /// How diverse the birds' genes were
+    #[serde(default)]
+    pub diversity: ga::Diversity,
+
+    /// Average distance between two birds' behaviours - as described by
+    /// novelty search's `Behaviour` when it's in use, by the birds' final
+    /// positions otherwise
+    #[serde(default)]
+    pub behavioural_diversity: f32,
+}
@@ -0,0 +1,30 @@
+use crate::*;
+
+/// Parameters that can be tweaked between any two steps (in the frontend,
+/// they come straight from the sliders).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepParams {
+    pub speed_min: f32,
+    pub speed_max: f32,
+    pub speed_accel: f32,
+    pub rotation_accel: f32,
+    pub generation_length: i32,
+    pub fov_range: f32,
+    pub fov_angle: f32,
+    pub cells: usize,
+}
+
+impl Default for StepParams {
+    fn default() -> Self {
+        Self {
+            speed_min: 0.001,
+            speed_max: 0.005,
+            speed_accel: 0.2,
+            rotation_accel: 1.5,
+            generation_length: 2500,
+            fov_range: 0.25,
+            fov_angle: 3.9,
+            cells: 9,
+        }
+    }
+}
@@ -1,9 +1,21 @@
 use crate::*;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct World {
     pub(crate) animals: Vec<Animal>,
     pub(crate) foods: Vec<Food>,
+
+    #[serde(default)]
+    pub(crate) arena: Arena,
+
+    pub(crate) food: FoodSupply,
+
+    #[serde(default)]
+    pub(crate) collisions: Collisions,
+
+    /// Identifier the next bird born in this world is going to get
+    #[serde(default)]
+    pub(crate) next_id: u64,
 }
 
 impl World {
@@ -15,11 +27,83 @@ impl World {
         fov_angle: f32,
         cells: usize,
     ) -> Self {
-        let animals = (0..animals).map(|_| Animal::random(rng, fov_range, fov_angle, cells)).collect();
+        let eye = Eye::default(fov_range, fov_angle, cells);
+
+        Self::populate(
+            rng,
+            Arena::default(),
+            FoodConfig {
+                count: foods as usize,
+                ..Default::default()
+            },
+            animals,
+            &eye,
+            &Brain::default_hidden(&eye),
+        )
+    }
+
+    /// Creates a world inside given arena, populated with random animals
+    /// having given eyes and brains, and with food behaving as configured.
+    pub fn populate(
+        rng: &mut dyn RngCore,
+        arena: Arena,
+        food: FoodConfig,
+        animals: i32,
+        eye: &Eye,
+        hidden: &[usize],
+    ) -> Self {
+        let animals: Vec<_> = (0..animals)
+            .map(|id| {
+                let mut animal = Animal::random(rng, eye.clone(), hidden);
+                animal.position = arena.place(rng, animal.position);
+                animal.id = id as u64;
+                animal
+            })
+            .collect();
+
+        let mut food = FoodSupply::new(rng, &arena, food);
+        let foods = food.populate(rng, &arena);
+
+        Self {
+            next_id: animals.len() as u64,
+            animals,
+            foods,
+            arena,
+            food,
+            collisions: Collisions::default(),
+        }
+    }
 
-        let foods = (0..foods).map(|_| Food::random(rng)).collect();
+    /// Gives a newborn bird the next free identifier and notes down its
+    /// parents (duplicates removed) and generation of birth.
+    pub(crate) fn register(&mut self, animal: &mut Animal, parents: &[u64], generation: u32) {
+        animal.id = self.next_id;
+        animal.parents = parents.to_vec();
+        animal.parents.dedup();
+        animal.generation = generation;
 
-        Self { animals, foods }
+        self.next_id += 1;
+    }
+
+    /// Makes sure birds born from now on get identifiers nobody has yet;
+    /// checkpoints saved before birds had identifiers come with all of them
+    /// set to zero, so such birds get numbered first.
+    pub(crate) fn restore_ids(&mut self) {
+        if self.next_id == 0 {
+            for (id, animal) in self.animals.iter_mut().enumerate() {
+                animal.id = id as u64;
+            }
+        }
+
+        let free = self.animals.iter().map(|animal| animal.id + 1).max();
+
+        self.next_id = self.next_id.max(free.unwrap_or(0));
+    }
+
+    /// Makes birds in this world bump into each other and compete for food.
+    pub fn with_collisions(mut self, collisions: Collisions) -> Self {
+        self.collisions = collisions;
+        self
     }
 
     pub fn animals(&self) -> &[Animal] {
@@ -29,4 +113,16 @@ impl World {
     pub fn foods(&self) -> &[Food] {
         &self.foods
     }
+
+    pub fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    pub fn food(&self) -> &FoodSupply {
+        &self.food
+    }
+
+    pub fn collisions(&self) -> &Collisions {
+        &self.collisions
+    }
 }
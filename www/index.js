@@ -1,7 +1,85 @@
-import * as sim from "lib-simulation-wasm";
+// The simulation itself runs in a worker (see `simulation-worker.js`), so
+// that stepping it doesn't make the page stutter; here we just send it
+// commands and draw whatever frames it sends back
+const simulation = new Worker("worker.js");
+
+// Latest frame received from the worker
+var frame = null;
+
+// Whether we're waiting for the worker to answer a step; we send the next
+// one only after the previous one is done, so that commands don't pile up
+// when the worker can't keep up
+var busy = false;
+
+simulation.onmessage = (message) => {
+  const event = message.data;
+
+  switch (event.type) {
+    case "frame":
+      frame = event;
+      busy = false;
+      break;
+
+    case "generation-finished": {
+      const stats = event.statistics;
+
+      document.getElementById("generation-stats").textContent =
+        `Last generation: avg ${stats.avg_fitness.toFixed(2)}, max ${stats.max_fitness.toFixed(2)}` +
+        `, diversity ${stats.diversity.toFixed(2)} (behavioural ${stats.behavioural_diversity.toFixed(2)})` +
+        (stats.meals.length > 1 ? `, eaten per kind ${stats.meals.join(" / ")}` : "");
+      break;
+    }
+
+    case "error":
+      showError(event.message);
+      busy = false;
+      break;
+
+    case "replay":
+      download(event.replay, "flai-replay.json");
+      break;
+
+    case "genome":
+      download(event.genome, "flai-bird.json");
+      break;
+
+    // Scenario overrides whatever the sliders say, so we move the sliders
+    // to match it
+    case "scenario":
+      for (const [slider, value] of [
+        ["speed-min", event.params.speed_min],
+        ["speed-max", event.params.speed_max],
+        ["speed-accel", event.params.speed_accel],
+        ["rotation-accel", event.params.rotation_accel],
+        ["generation-length", event.params.generation_length],
+        ["fov-range", event.params.fov_range],
+        ["fov-angle", event.params.fov_angle],
+        ["cells", event.params.cells],
+        ["num-animals", event.animals],
+        ["num-foods", event.foods],
+      ]) {
+        document.getElementById(slider).value = value;
+      }
+      break;
+  }
+};
+
+function showError(message) {
+  document.getElementById("simulation-error").textContent = message;
+}
+
+function download(contents, name) {
+  const file = new Blob([contents], { type: "application/json" });
+  const link = document.createElement("a");
+
+  link.href = URL.createObjectURL(file);
+  link.download = name;
+  link.click();
 
-var simulation;
-function restart() {
+  URL.revokeObjectURL(link.href);
+}
+
+function restart(genome) {
   
   var sliderValueAnimal = document.getElementById("num-animals").value;
   var sliderValueFoods = document.getElementById("num-foods").value;
@@ -12,13 +90,108 @@ function restart() {
   console.log("Button pressed");
 
   
-  simulation = new sim.Simulation(sliderValueAnimal, sliderValueFoods, sliderValueFOVRange, sliderValueFOVAngle, sliderValueCells);
+  simulation.postMessage({
+    type: "configure",
+    animals: Number(sliderValueAnimal),
+    foods: Number(sliderValueFoods),
+    fov_range: Number(sliderValueFOVRange),
+    fov_angle: Number(sliderValueFOVAngle),
+    cells: Number(sliderValueCells),
+    genome,
+  });
 }
 
 restart();
 
 const button = document.getElementById("custom-button");
 
+button.addEventListener("click", () => restart());
+
+document.getElementById("export-replay-button").addEventListener("click", () => {
+  simulation.postMessage({ type: "export-replay" });
+});
+
+// Exports the selected bird or, if none is selected, the best one
+document.getElementById("export-genome-button").addEventListener("click", () => {
+  simulation.postMessage({
+    type: "export-genome",
+    animal: selectedAnimal === null ? undefined : selectedAnimal,
+  });
+});
+
+const loadScenarioInput = document.getElementById("load-scenario-input");
+
+document.getElementById("load-scenario-button").addEventListener("click", () => {
+  loadScenarioInput.click();
+});
+
+loadScenarioInput.addEventListener("change", async () => {
+  const file = loadScenarioInput.files[0];
+
+  if (!file) {
+    return;
+  }
+
+  const scenario = await file.text();
+
+  loadScenarioInput.value = "";
+  simulation.postMessage({ type: "load-scenario", scenario });
+});
+
+const importGenomeInput = document.getElementById("import-genome-input");
+
+document.getElementById("import-genome-button").addEventListener("click", () => {
+  importGenomeInput.click();
+});
+
+importGenomeInput.addEventListener("change", async () => {
+  const file = importGenomeInput.files[0];
+
+  if (!file) {
+    return;
+  }
+
+  const genome = await file.text();
+
+  importGenomeInput.value = "";
+
+  let eye;
+
+  try {
+    eye = JSON.parse(genome).eye;
+  } catch (e) {
+    showError(`Couldn't import genome: ${e}`);
+    return;
+  }
+
+  if (!eye) {
+    showError("Couldn't import genome: it has no eye");
+    return;
+  }
+
+  // A bird can join the current population only if its brain has the same
+  // shape as everybody else's - otherwise we start over with its eye
+  if (eye.cells == document.getElementById("cells").value) {
+    simulation.postMessage({ type: "inject", genome });
+  } else {
+    document.getElementById("fov-range").value = eye.fov_range;
+    document.getElementById("fov-angle").value = eye.fov_angle;
+    document.getElementById("cells").value = eye.cells;
+
+    restart(genome);
+  }
+});
+
+// When fast-forwarding, each frame spends most of its time budget on
+// stepping the simulation headlessly instead of just a single step
+const FAST_FORWARD_MILLIS = 12;
+var fastForward = false;
+
+document.getElementById("fast-forward-button").addEventListener("click", (event) => {
+  fastForward = !fastForward;
+  event.target.classList.toggle("active", fastForward);
+});
+
 
 const viewport = document.getElementById('viewport');
 const viewportWidth = viewport.width;
@@ -35,6 +208,34 @@ viewport.style.height = viewportHeight + 'px';
 // canvas rendering context
 const ctxt = viewport.getContext('2d');
 
+// Index of the bird whose eye & brain are being inspected (if any)
+var selectedAnimal = null;
+
+viewport.addEventListener("click", (event) => {
+  const rect = viewport.getBoundingClientRect();
+  const x = (event.clientX - rect.left) / viewportWidth;
+  const y = (event.clientY - rect.top) / viewportHeight;
+  selectedAnimal = null;
+
+  if (frame === null) {
+    return;
+  }
+
+  const stride = frame.animal_stride;
+  const animals = frame.animals;
+
+  var selectedDistance = 0.03;
+
+  for (let i = 0; i < animals.length; i += stride) {
+    const distance = Math.hypot(animals[i] - x, animals[i + 1] - y);
+
+    if (distance < selectedDistance) {
+      selectedAnimal = i / stride;
+      selectedDistance = distance;
+    }
+  }
+});
+
 CanvasRenderingContext2D.prototype.drawTriangle =
     function (x, y, size, rotation) {
         this.beginPath();
@@ -63,13 +264,46 @@ CanvasRenderingContext2D.prototype.drawTriangle =
         this.stroke();
     };
 
+CanvasRenderingContext2D.prototype.drawFov =
+    function (animal, width, height) {
+        const x = animal.x * width;
+        const y = animal.y * height;
+        const range = animal.fov_range * width;
+        const vision = animal.vision;
+        const cells = vision.length / animal.channels;
+        const cellAngle = animal.fov_angle / cells;
+
+        // Cells go from bird's left to its right; the canvas' angles are
+        // measured from the x axis, while bird's rotation is measured from
+        // the y axis, hence the `+ PI / 2`
+        const start = animal.rotation + Math.PI / 2.0 - animal.fov_angle / 2.0;
+
+        for (let i = 0; i < cells; i += 1) {
+            this.beginPath();
+            this.moveTo(x, y);
+            this.arc(x, y, range, start + i * cellAngle, start + (i + 1) * cellAngle);
+            this.closePath();
+
+            // Each cell sees each kind of food separately, but here we
+            // just show how much it sees in total
+            let seen = 0.0;
+
+            for (let channel = 0; channel < animal.channels; channel += 1) {
+                seen += vision[i * animal.channels + channel];
+            }
+
+            this.fillStyle = `rgba(255, 255, 128, ${0.1 + 0.6 * Math.min(seen, 1.0)})`;
+            this.fill();
+        }
+    };
+
 CanvasRenderingContext2D.prototype.drawCircle =
-    function(x, y, radius) {
+    function(x, y, radius, color = 'rgb(0, 255, 128)') {
         this.beginPath();
 
         this.arc(x, y, radius, 0, 2.0 * Math.PI);
 
-        this.fillStyle = 'rgb(0, 255, 128)';
+        this.fillStyle = color;
         this.fill();
     };
 
@@ -93,42 +327,34 @@ function redraw() {
       document.getElementById("speed-min").value = sliderValueSpeedMax - 0.0005;
     }
 
-    simulation.step(
-      sliderValueSpeedMin,
-      sliderValueSpeedMax,
-      sliderValueSpeedAccel,
-      sliderValueRotationAccel,
-      sliderValueGenerationLength,
-      sliderValueFOVRange,
-      sliderValueFOVAngle,
-      sliderValueCells,
-    );
+    const params = {
+      speed_min: Number(sliderValueSpeedMin),
+      speed_max: Number(sliderValueSpeedMax),
+      speed_accel: Number(sliderValueSpeedAccel),
+      rotation_accel: Number(sliderValueRotationAccel),
+      generation_length: Number(sliderValueGenerationLength),
+      fov_range: Number(sliderValueFOVRange),
+      fov_angle: Number(sliderValueFOVAngle),
+      cells: Number(sliderValueCells),
+    };
 
-    var spanGeneration = document.getElementById("gen-num");
-    spanGeneration.textContent = simulation.get_generation();
+    const inspect = selectedAnimal === null ? undefined : selectedAnimal;
 
-    const world = simulation.world();
+    if (!busy) {
+      busy = true;
 
-    for (const food of world.foods) {
-        ctxt.drawCircle(
-            food.x * viewportWidth,
-            food.y * viewportHeight,
-            (0.01 / 2.0) * viewportWidth,
-        );
+      if (fastForward) {
+        simulation.postMessage({ type: "fast-forward", params, millis: FAST_FORWARD_MILLIS, inspect });
+      } else {
+        simulation.postMessage({ type: "step", params, inspect });
+      }
     }
 
-    for (const animal of world.animals) {
-        ctxt.drawTriangle(
-            animal.x * viewportWidth,
-            animal.y * viewportHeight,
-            0.01 * viewportWidth,
-            animal.rotation,
-        );
+    if (frame !== null) {
+      draw(frame);
     }
 
 
-    button.addEventListener("click", restart);
-
     // requestAnimationFrame() schedules code only for the next frame.
     //
     // Because we want for our simulation to continue forever, we've
@@ -136,5 +362,60 @@ function redraw() {
     requestAnimationFrame(redraw);
 }
 
+function draw(frame) {
+    var spanGeneration = document.getElementById("gen-num");
+    spanGeneration.textContent = frame.generation;
+
+    const animal = frame.inspected;
+    const animalInfo = document.getElementById("selected-animal");
+
+    if (animal) {
+      ctxt.drawFov(animal, viewportWidth, viewportHeight);
+
+      animalInfo.textContent =
+        `Selected bird: satiation ${animal.satiation.toFixed(1)}, ` +
+        `speed ${animal.output[0].toFixed(3)}, rotation ${animal.output[1].toFixed(3)}`;
+    } else {
+      animalInfo.textContent = "";
+    }
+
+    const obstacleStride = frame.obstacle_stride;
+    const obstacles = frame.obstacles;
+
+    for (let i = 0; i < obstacles.length; i += obstacleStride) {
+        ctxt.drawCircle(
+            obstacles[i] * viewportWidth,
+            obstacles[i + 1] * viewportHeight,
+            obstacles[i + 2] * viewportWidth,
+            'rgb(108, 117, 125)',
+        );
+    }
+
+    const foodStride = frame.food_stride;
+    const foods = frame.foods;
+
+    for (let i = 0; i < foods.length; i += foodStride) {
+        ctxt.drawCircle(
+            foods[i] * viewportWidth,
+            foods[i + 1] * viewportHeight,
+            (0.01 / 2.0) * viewportWidth,
+            // Poison is red
+            foods[i + 2] < 0.0 ? 'rgb(255, 64, 64)' : undefined,
+        );
+    }
+
+    const animalStride = frame.animal_stride;
+    const animals = frame.animals;
+
+    for (let i = 0; i < animals.length; i += animalStride) {
+        ctxt.drawTriangle(
+            animals[i] * viewportWidth,
+            animals[i + 1] * viewportHeight,
+            0.01 * viewportWidth,
+            animals[i + 2],
+        );
+    }
+}
+
 redraw();
 
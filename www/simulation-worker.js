@@ -0,0 +1,22 @@
+import * as sim from "lib-simulation-wasm";
+
+// See `Worker` in `lib-simulation-wasm` for the commands & events
+const worker = new sim.Worker();
+
+export function handle(message) {
+  let events;
+
+  try {
+    events = worker.handle(message.data);
+  } catch (e) {
+    // Let the page know, so that it doesn't keep waiting for an answer
+    postMessage({ type: "error", message: `Couldn't handle ${message.data.type}: ${e}` });
+    return;
+  }
+
+  for (const event of events) {
+    // Frames come with freshly allocated buffers, so instead of copying
+    // them, we can hand them over to the page
+    postMessage(event, sim.Worker.transferables(event));
+  }
+}
@@ -1,14 +1,28 @@
 const CopyWebpackPlugin = require("copy-webpack-plugin");
 const path = require('path');
 
-module.exports = {
-  entry: "./bootstrap.js",
-  output: {
-    path: path.resolve(__dirname, "dist"),
-    filename: "bootstrap.js",
+module.exports = [
+  {
+    entry: "./bootstrap.js",
+    output: {
+      path: path.resolve(__dirname, "dist"),
+      filename: "bootstrap.js",
+    },
+    mode: "development",
+    plugins: [
+      new CopyWebpackPlugin(['index.html'])
+    ],
   },
-  mode: "development",
-  plugins: [
-    new CopyWebpackPlugin(['index.html'])
-  ],
-};
+
+  // The simulation runs in a worker, which has to be bundled separately,
+  // since workers load chunks (and wasm) differently than pages do
+  {
+    entry: "./worker-bootstrap.js",
+    target: "webworker",
+    output: {
+      path: path.resolve(__dirname, "dist"),
+      filename: "worker.js",
+    },
+    mode: "development",
+  },
+];
@@ -0,0 +1,14 @@
+// Same as `bootstrap.js`, but for the simulation worker - since importing
+// wasm takes a while, messages that arrive in the meantime are queued and
+// handled once everything's loaded.
+
+const queue = [];
+
+self.onmessage = (message) => queue.push(message);
+
+import("./simulation-worker.js")
+  .then(({ handle }) => {
+    self.onmessage = handle;
+    queue.forEach(handle);
+  })
+  .catch(e => console.error("Error importing `simulation-worker.js`:", e));